@@ -0,0 +1,22 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_FOLDER: &str = "dijkstra_maze";
+
+// Папка настроек пользователя: %APPDATA% на Windows, ~/.config в остальных системах
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("APPDATA")
+        .or_else(|| env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join(APP_FOLDER))
+}
+
+// Папка данных пользователя (таблица рекордов): %LOCALAPPDATA% на Windows, ~/.local/share в остальных системах
+pub fn data_dir() -> Option<PathBuf> {
+    let base = env::var_os("LOCALAPPDATA")
+        .or_else(|| env::var_os("XDG_DATA_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(base.join(APP_FOLDER))
+}
@@ -1,16 +1,23 @@
-use piston_window::types::Color;
-use piston_window::*;
-use rand::{thread_rng, Rng};
+use std::collections::VecDeque;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
 use crate::snake::{Direction, Snake};
-use crate::drawing::{draw_block, draw_rectange};
+use crate::high_scores::{self, HighScore};
 
-const FOOD_COLOR: Color = [0.90, 0.49, 0.13, 1.0];
-const BORDER_COLOR: Color = [0.741, 0.765, 0.78, 1.0];
-const GAMEOVER_COLOR: Color = [0.91, 0.30, 0.24, 0.5];
+mod autopilot;
+mod difficulty;
+mod headless;
+#[cfg(feature = "gui")]
+mod hud;
+#[cfg(feature = "gui")]
+mod input;
+mod scores;
+
+pub use difficulty::Difficulty;
 
-const MOVING_PERIOD: f64 = 0.2;
 const RESTART_TIME: f64 = 1.0; 
+// Сколько поворотов можно нажать наперёд
+const INPUT_QUEUE_LIMIT: usize = 3;
 impl Game {
     pub fn get_dimensions(&self) -> (i32, i32) {
         (self.width, self.height) // Предположим, что у вас есть width и height как поля структуры
@@ -27,72 +34,115 @@ pub struct Game {
     height: i32,
 
     is_game_over: bool,
+    // Окно открывается перед забегом: змейка стоит, пока игрок выбирает скорость
+    is_ready: bool,
     waiting_time: f64,
+
+    autopilot: bool,
+    // Вёл ли автопилот змейку хоть раз за этот забег: такой забег в рекорды не попадает
+    autopilot_used: bool,
+    autopilot_path: Vec<(i32, i32)>,
+
+    // Нажатые повороты: каждый такт забирает по одному
+    input_queue: VecDeque<Direction>,
+
+    difficulty: Difficulty,
+    speed_ramp: bool,
+    min_period: f64,
+
+    score: u32,
+    // Рекорды ведёт только игра в окне; initials — инициалы, которые вводит игрок после рекорда
+    record_scores: bool,
+    high_scores: Vec<HighScore>,
+    initials: Option<String>,
+
+    rng: StdRng,
 }
 
 impl Game {
     pub fn new(width: i32, height: i32) -> Game {
+        let mut game = Game::with_seed(width, height, thread_rng().gen());
+        game.record_scores = true;
+        game.is_ready = true;
+        game.high_scores = high_scores::load();
+        game
+    }
+
+    // С одним и тем же зерном еда появляется в тех же местах — игру можно воспроизвести
+    pub fn with_seed(width: i32, height: i32, seed: u64) -> Game {
         Game {
             snake: Snake::new(2, 2),
             waiting_time: 0.0,
             food_exist: true,
             food_x: 5,
             food_y: 3,
-            width: width,
-            height: height,
+            width,
+            height,
             is_game_over: false,
+            is_ready: false,
+            autopilot: false,
+            autopilot_used: false,
+            autopilot_path: Vec::new(),
+            input_queue: VecDeque::new(),
+            difficulty: Difficulty::Normal,
+            speed_ramp: false,
+            min_period: 0.08,
+            score: 0,
+            record_scores: false,
+            high_scores: Vec::new(),
+            initials: None,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
-    pub fn key_pressed(&mut self, key: Key) {
-        if self.is_game_over {
+    // Включает и выключает автопилот; пока он ведёт змейку, повороты игрока не действуют
+    pub fn toggle_autopilot(&mut self) {
+        self.autopilot = !self.autopilot;
+        self.autopilot_used |= self.autopilot;
+        self.autopilot_path.clear();
+        self.input_queue.clear();
+    }
+
+    // Поворот игрока: встаёт в очередь и выполняется на одном из следующих тактов
+    pub fn turn(&mut self, dir: Direction) {
+        if self.is_game_over || self.autopilot {
             return;
         }
-
-        let dir = match key {
-            Key::Up => Some(Direction::Up),
-            Key::Down => Some(Direction::Down),
-            Key::Left => Some(Direction::Left),
-            Key::Right => Some(Direction::Right),
-            _ => return,
-        };
-
-        if dir.unwrap() == self.snake.head_direction().opposite() {
+        // Поворот сверяем с последним поворотом в очереди, а не с текущим направлением,
+        // иначе два быстрых нажатия развернут змейку в саму себя
+        let last = self.input_queue.back().copied().unwrap_or(self.snake.head_direction());
+        if dir == last || dir == last.opposite() || self.input_queue.len() >= INPUT_QUEUE_LIMIT {
             return;
         }
-
-        self.update_snake(dir);
+        self.input_queue.push_back(dir);
     }
 
-    pub fn draw(&self, con: &Context, g: &mut G2d) {
-        self.snake.draw(con, g);
-
-        if self.food_exist {
-            draw_block(FOOD_COLOR, self.food_x, self.food_y, con, g);
-        }
-
-        draw_rectange(BORDER_COLOR, 0, 0, self.width, 1, con, g);
-        draw_rectange(BORDER_COLOR, 0, self.height - 1, self.width, 1, con, g);
-        draw_rectange(BORDER_COLOR, 0, 0, 1, self.height, con, g);
-        draw_rectange(BORDER_COLOR, self.width - 1, 0, 1, self.height, con, g);
-        if self.is_game_over {
-            draw_rectange(GAMEOVER_COLOR, 0, 0, self.width, self.height, con, g);
-        }
+    // Начинает забег, которого ждёт только что открытое окно
+    pub fn start(&mut self) {
+        self.is_ready = false;
+        self.waiting_time = 0.0;
     }
 
     pub fn update(&mut self, delta_time: f64) {
         self.waiting_time += delta_time;
+        // Игрок после конца игры смотрит таблицу рекордов и сам начинает заново,
+        // автопилот перезапускается через RESTART_TIME
         if self.is_game_over {
-            if self.waiting_time > RESTART_TIME {
+            if self.autopilot && self.waiting_time > RESTART_TIME {
                 self.restart();
             }
             return;
         }
+        if self.is_ready {
+            self.waiting_time = 0.0;
+            return;
+        }
         if !self.food_exist {
             self.add_food();
         }
-        if self.waiting_time > MOVING_PERIOD {
-            self.update_snake(None);
+        if self.waiting_time > self.moving_period() {
+            let dir = if self.autopilot { self.autopilot_direction() } else { self.input_queue.pop_front() };
+            self.update_snake(dir);
         }
     }
 
@@ -101,6 +151,7 @@ impl Game {
         if self.food_exist && self.food_x == head_x && self.food_y == head_y {
             self.food_exist = false;
             self.snake.restore_last_removed();
+            self.score += self.difficulty.points();
         }
     }
 
@@ -113,12 +164,23 @@ impl Game {
     }
 
     fn add_food(&mut self) {
-        let mut rng = thread_rng();
-        let mut new_x = rng.gen_range(1..(self.width - 1));
-        let mut new_y = rng.gen_range(1..(self.height - 1));
-        while self.snake.is_overlap_except_tail(new_x, new_y) {
-            new_x = rng.gen_range(1..(self.width - 1));
-            new_y = rng.gen_range(1..(self.height - 1));
+        // Еда не появляется ни на одной клетке тела, даже на хвосте: съев её там, змейка
+        // выросла бы прямо на собственный хвост. Если тело заняло всё поле, игра окончена.
+        let body = self.snake.positions();
+        let free_cells = (1..(self.width - 1))
+            .flat_map(|x| (1..(self.height - 1)).map(move |y| (x, y)))
+            .filter(|cell| !body.contains(cell))
+            .count();
+        if free_cells == 0 {
+            self.end_game();
+            return;
+        }
+
+        let mut new_x = self.rng.gen_range(1..(self.width - 1));
+        let mut new_y = self.rng.gen_range(1..(self.height - 1));
+        while body.contains(&(new_x, new_y)) {
+            new_x = self.rng.gen_range(1..(self.width - 1));
+            new_y = self.rng.gen_range(1..(self.height - 1));
         }
         self.food_x = new_x;
         self.food_y = new_y;
@@ -130,7 +192,7 @@ impl Game {
             self.snake.move_forward(dir);
             self.check_eating();
         } else {
-            self.is_game_over = true;
+            self.end_game();
         }
         self.waiting_time = 0.0;
     }
@@ -142,5 +204,11 @@ impl Game {
         self.food_x = 5;
         self.food_y = 3;
         self.is_game_over = false;
+        self.is_ready = false;
+        self.autopilot_used = self.autopilot;
+        self.autopilot_path.clear();
+        self.input_queue.clear();
+        self.score = 0;
+        self.initials = None;
     }
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use crate::pathfinding::{self, Graph};
+use crate::snake::Direction;
+
+use super::Game;
+
+// Поле змейки: клетки внутри рамки, кроме занятых телом
+struct Board<'a> {
+    width: i32,
+    height: i32,
+    blocked: &'a HashSet<(i32, i32)>,
+}
+
+impl Graph for Board<'_> {
+    type Node = (i32, i32);
+
+    fn neighbors(&self, (x, y): (i32, i32)) -> impl Iterator<Item = ((i32, i32), f64)> {
+        [(x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y)]
+            .into_iter()
+            .filter(move |&(nx, ny)| nx > 0 && ny > 0 && nx < self.width - 1 && ny < self.height - 1)
+            .filter(move |cell| !self.blocked.contains(cell))
+            .map(|cell| (cell, 1.0))
+    }
+}
+
+fn direction_to(from: (i32, i32), to: (i32, i32)) -> Direction {
+    match (to.0 - from.0, to.1 - from.1) {
+        (0, -1) => Direction::Up,
+        (0, 1) => Direction::Down,
+        (-1, _) => Direction::Left,
+        _ => Direction::Right,
+    }
+}
+
+impl Game {
+    // Путь от головы до цели; хвост к следующему ходу освободится, поэтому он не препятствие
+    fn path_on(&self, body: &[(i32, i32)], goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        let blocked: HashSet<(i32, i32)> = body[1..body.len() - 1].iter().copied().collect();
+        let board = Board { width: self.width, height: self.height, blocked: &blocked };
+        pathfinding::bfs(&board, body[0], |cell| cell == goal)
+    }
+
+    // Тело после прохода по path; на клетке с едой змейка вырастает, как в check_eating
+    fn body_after(&self, body: &[(i32, i32)], path: &[(i32, i32)]) -> Vec<(i32, i32)> {
+        let mut body = body.to_vec();
+        for &cell in path {
+            body.insert(0, cell);
+            if !(self.food_exist && cell == (self.food_x, self.food_y)) {
+                body.pop();
+            }
+        }
+        body
+    }
+
+    // Путь от головы к хвосту хотя бы в один настоящий шаг: голова на месте хвоста —
+    // это столкновение, а не безопасная позиция
+    fn path_to_tail(&self, body: &[(i32, i32)]) -> Option<Vec<(i32, i32)>> {
+        self.path_on(body, body[body.len() - 1]).filter(|path| path.len() > 1)
+    }
+
+    // Положение безопасно, если голова может догнать свой хвост
+    fn is_safe(&self, body: &[(i32, i32)]) -> bool {
+        body.len() < 3 || self.path_to_tail(body).is_some()
+    }
+
+    // Ход автопилота: кратчайший путь к еде, если после него змейка не запрёт себя;
+    // иначе погоня за хвостом по самому длинному безопасному ходу; иначе любой ход, не убивающий сразу
+    pub(super) fn autopilot_direction(&mut self) -> Option<Direction> {
+        let body = self.snake.positions();
+        let head = body[0];
+        self.autopilot_path.clear();
+
+        if self.food_exist {
+            if let Some(path) = self.path_on(&body, (self.food_x, self.food_y)) {
+                if self.is_safe(&self.body_after(&body, &path[1..])) {
+                    let direction = direction_to(head, path[1]);
+                    self.autopilot_path = path;
+                    return Some(direction);
+                }
+            }
+        }
+
+        let moves: Vec<(i32, i32)> = [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+            .into_iter()
+            .filter(|&direction| self.check_if_the_snake_alive(Some(direction)))
+            .map(|direction| self.snake.next_head_position(Some(direction)))
+            .collect();
+
+        let chase = moves
+            .iter()
+            .filter_map(|&next| {
+                let after = self.body_after(&body, &[next]);
+                let to_tail = self.path_to_tail(&after)?;
+                Some((to_tail.len(), next, to_tail))
+            })
+            .max_by_key(|(length, _, _)| *length);
+        if let Some((_, next, to_tail)) = chase {
+            self.autopilot_path = std::iter::once(head).chain(to_tail).collect();
+            return Some(direction_to(head, next));
+        }
+
+        moves.first().map(|&next| direction_to(head, next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Змейка начинает в безопасном положении, и автопилот обязан его сохранять: безопасный ход
+    // у него есть всегда, поэтому игра может закончиться только заполненным полем, а не гибелью
+    #[test]
+    fn autopilot_never_dies_while_it_has_a_safe_move() {
+        for seed in [0, 1, 2, 9, 10, 11, 16, 17, 19, 26] {
+            let mut game = Game::with_seed(12, 12, seed);
+            for _ in 0..2000 {
+                let direction = game.autopilot_direction();
+                let result = game.step(direction);
+                if result.done {
+                    assert!(result.reward > 0.0, "seed {}: погибла на длине {}", seed, game.length());
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use super::Game;
+
+// Во сколько раз укорачивается такт за каждую съеденную еду при включённом разгоне
+const RAMP_FACTOR: f64 = 0.97;
+pub const MIN_PERIOD_LIMIT: f64 = 0.03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    // Начальная длительность такта, в секундах
+    pub fn period(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Normal => 0.2,
+            Difficulty::Hard => 0.12,
+            Difficulty::Insane => 0.07,
+        }
+    }
+
+    // Очки за каждую съеденную еду: чем быстрее игра, тем дороже
+    pub fn points(self) -> u32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3,
+            Difficulty::Insane => 5,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "лёгкий",
+            Difficulty::Normal => "обычный",
+            Difficulty::Hard => "сложный",
+            Difficulty::Insane => "безумный",
+        }
+    }
+}
+
+impl Game {
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.min_period = self.min_period.min(difficulty.period());
+    }
+
+    // Разгон: такт короче с каждой съеденной едой, но не короче min_period
+    pub fn set_speed_ramp(&mut self, enabled: bool, min_period: f64) {
+        self.speed_ramp = enabled;
+        self.min_period = min_period.clamp(MIN_PERIOD_LIMIT, self.difficulty.period());
+    }
+
+    pub fn moving_period(&self) -> f64 {
+        let base = self.difficulty.period();
+        if !self.speed_ramp {
+            return base;
+        }
+        let eaten = self.snake.positions().len().saturating_sub(3) as i32;
+        (base * RAMP_FACTOR.powi(eaten)).max(self.min_period)
+    }
+
+    // Скорость в клетках в секунду — её и показываем игроку
+    pub fn speed(&self) -> f64 {
+        1.0 / self.moving_period()
+    }
+}
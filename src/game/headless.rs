@@ -0,0 +1,97 @@
+use crate::snake::{Direction, Tile};
+
+use super::Game;
+
+const FOOD_REWARD: f64 = 1.0;
+const DEATH_REWARD: f64 = -1.0;
+
+// Итог одного такта: награда, закончена ли игра, и поле после хода
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult {
+    pub reward: f64,
+    pub done: bool,
+    pub observation: Vec<Vec<Tile>>,
+}
+
+// Пошаговое управление без окна и таймера: каждый вызов step — ровно один такт игры
+impl Game {
+    // action — новое направление или None, чтобы ползти прямо. Разворот назад игнорируется.
+    // После конца игры step ничего не меняет, пока не вызван reset.
+    pub fn step(&mut self, action: Option<Direction>) -> StepResult {
+        if self.is_game_over {
+            return StepResult { reward: 0.0, done: true, observation: self.observation() };
+        }
+        self.is_ready = false;
+        if !self.food_exist {
+            self.add_food();
+        }
+
+        let action = action.filter(|&dir| dir != self.snake.head_direction().opposite());
+        let was_food = self.food_exist;
+        self.update_snake(action);
+
+        let reward = if self.is_game_over {
+            DEATH_REWARD
+        } else if was_food && !self.food_exist {
+            self.add_food();
+            FOOD_REWARD
+        } else {
+            0.0
+        };
+        StepResult { reward, done: self.is_game_over, observation: self.observation() }
+    }
+
+    pub fn reset(&mut self) -> Vec<Vec<Tile>> {
+        self.restart();
+        self.observation()
+    }
+
+    // Поле строками сверху вниз: observation[y][x]
+    pub fn observation(&self) -> Vec<Vec<Tile>> {
+        let mut grid = vec![vec![Tile::Empty; self.width as usize]; self.height as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1 {
+                    grid[y as usize][x as usize] = Tile::Wall;
+                }
+            }
+        }
+        if self.food_exist {
+            grid[self.food_y as usize][self.food_x as usize] = Tile::Food;
+        }
+        for (index, (x, y)) in self.snake.positions().into_iter().enumerate() {
+            if x >= 0 && y >= 0 && x < self.width && y < self.height {
+                grid[y as usize][x as usize] = if index == 0 { Tile::SnakeHead } else { Tile::Snake };
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Автопилот ест еду, поэтому в прогоне участвует и генератор случайных чисел
+    fn play(seed: u64) -> Vec<StepResult> {
+        let mut game = Game::with_seed(12, 12, seed);
+        game.reset();
+        let mut results = Vec::new();
+        for turn in 0..300 {
+            let action = if turn % 7 == 0 { Some(Direction::Down) } else { game.autopilot_direction() };
+            let result = game.step(action);
+            if result.done {
+                game.reset();
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    #[test]
+    fn same_seed_and_actions_repeat_the_game() {
+        let first = play(7);
+        assert!(first.iter().any(|result| result.reward > 0.0));
+        assert_eq!(first, play(7));
+    }
+}
@@ -0,0 +1,90 @@
+use piston_window::types::Color;
+use piston_window::*;
+
+use crate::drawing::{draw_block, draw_rectange, to_gui_coord};
+
+use super::Game;
+
+const FOOD_COLOR: Color = [0.90, 0.49, 0.13, 1.0];
+const BORDER_COLOR: Color = [0.741, 0.765, 0.78, 1.0];
+const GAMEOVER_COLOR: Color = [0.91, 0.30, 0.24, 0.5];
+const AUTOPILOT_COLOR: Color = [0.95, 0.77, 0.06, 0.3];
+const HUD_COLOR: Color = [0.204, 0.286, 0.369, 1.0];
+const TABLE_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+const HUD_FONT_SIZE: u32 = 12;
+
+// Рисование игры в окне piston; сама игра от него не зависит
+impl Game {
+    pub fn draw(&self, con: &Context, g: &mut G2d) {
+        for &(x, y) in self.autopilot_path.iter().skip(1) {
+            draw_block(AUTOPILOT_COLOR, x, y, con, g);
+        }
+        self.snake.draw(con, g);
+
+        if self.food_exist {
+            draw_block(FOOD_COLOR, self.food_x, self.food_y, con, g);
+        }
+
+        draw_rectange(BORDER_COLOR, 0, 0, self.width, 1, con, g);
+        draw_rectange(BORDER_COLOR, 0, self.height - 1, self.width, 1, con, g);
+        draw_rectange(BORDER_COLOR, 0, 0, 1, self.height, con, g);
+        draw_rectange(BORDER_COLOR, self.width - 1, 0, 1, self.height, con, g);
+        if self.is_game_over {
+            draw_rectange(GAMEOVER_COLOR, 0, 0, self.width, self.height, con, g);
+        }
+    }
+
+    // Строки состояния поверх рамки: сверху сложность и скорость, снизу счёт и длина.
+    // После конца игры — таблица рекордов и ввод инициалов.
+    pub fn draw_hud(&self, con: &Context, g: &mut G2d, glyphs: &mut Glyphs) {
+        let ramp = if self.speed_ramp {
+            format!("разгон до {:.1}", 1.0 / self.min_period)
+        } else {
+            "без разгона".to_owned()
+        };
+        let top = format!("{}  {:.1} кл/с  {}", self.difficulty.name(), self.speed(), ramp);
+        draw_line(&top, 1, 1, HUD_COLOR, con, g, glyphs);
+        let bottom = format!("Счёт: {}  Длина: {}", self.score(), self.length());
+        draw_line(&bottom, 1, self.height, HUD_COLOR, con, g, glyphs);
+
+        if self.is_ready {
+            let lines = ["Пробел или стрелка — начать", "1–4 — сложность, R — разгон, -/= — его предел"];
+            for (index, line) in lines.iter().enumerate() {
+                draw_line(line, 2, 3 + index as i32, TABLE_COLOR, con, g, glyphs);
+            }
+        }
+        if self.is_game_over {
+            self.draw_high_scores(con, g, glyphs);
+        }
+    }
+
+    fn draw_high_scores(&self, con: &Context, g: &mut G2d, glyphs: &mut Glyphs) {
+        let mut lines = vec!["Рекорды".to_owned()];
+        if self.high_scores.is_empty() {
+            lines.push("пока пусто".to_owned());
+        }
+        for (place, entry) in self.high_scores.iter().enumerate() {
+            lines.push(format!("{:>2}. {:<3} {:>5}  {:<8} {}", place + 1, entry.initials, entry.score, entry.difficulty.name(), entry.date));
+        }
+        lines.push(String::new());
+        match &self.initials {
+            Some(initials) => lines.push(format!("Новый рекорд! Инициалы: {}_  (Enter)", initials)),
+            None => {
+                lines.push("Пробел — новая игра".to_owned());
+                lines.push("1–4 — сложность, R — разгон, -/= — его предел".to_owned());
+            }
+        }
+
+        for (index, line) in lines.iter().enumerate() {
+            draw_line(line, 2, 3 + index as i32, TABLE_COLOR, con, g, glyphs);
+        }
+    }
+}
+
+// Текст по сетке поля: строка ложится на нижнюю часть клетки (x, y - 1)
+fn draw_line(line: &str, x: i32, y: i32, color: Color, con: &Context, g: &mut G2d, glyphs: &mut Glyphs) {
+    let transform = con.transform.trans(to_gui_coord(x), to_gui_coord(y) - 7.0);
+    text::Text::new_color(color, HUD_FONT_SIZE)
+        .draw(line, glyphs, &con.draw_state, transform, g)
+        .ok();
+}
@@ -0,0 +1,136 @@
+use piston_window::Key;
+
+use crate::snake::Direction;
+
+use super::{Difficulty, Game};
+
+// Шаг, с которым клавишами меняется минимальный такт
+const MIN_PERIOD_STEP: f64 = 0.01;
+
+fn letter(key: Key) -> Option<char> {
+    let letters = [
+        (Key::A, 'A'), (Key::B, 'B'), (Key::C, 'C'), (Key::D, 'D'), (Key::E, 'E'), (Key::F, 'F'),
+        (Key::G, 'G'), (Key::H, 'H'), (Key::I, 'I'), (Key::J, 'J'), (Key::K, 'K'), (Key::L, 'L'),
+        (Key::M, 'M'), (Key::N, 'N'), (Key::O, 'O'), (Key::P, 'P'), (Key::Q, 'Q'), (Key::R, 'R'),
+        (Key::S, 'S'), (Key::T, 'T'), (Key::U, 'U'), (Key::V, 'V'), (Key::W, 'W'), (Key::X, 'X'),
+        (Key::Y, 'Y'), (Key::Z, 'Z'),
+    ];
+    letters.iter().find(|(k, _)| *k == key).map(|&(_, c)| c)
+}
+
+// Клавиатура окна piston: клавиши переводятся в команды игры
+impl Game {
+    pub fn key_pressed(&mut self, key: Key) {
+        if self.is_game_over {
+            self.game_over_key(key);
+            return;
+        }
+        // Перед первым забегом работают клавиши скорости, любая другая начинает игру
+        if self.is_ready {
+            if self.speed_key(key) {
+                return;
+            }
+            self.start();
+        }
+
+        if key == Key::A {
+            self.toggle_autopilot();
+            return;
+        }
+        let dir = match key {
+            Key::Up => Direction::Up,
+            Key::Down => Direction::Down,
+            Key::Left => Direction::Left,
+            Key::Right => Direction::Right,
+            _ => return,
+        };
+        self.turn(dir);
+    }
+
+    // Клавиши после конца игры: ввод инициалов, затем настройка скорости и пробел или Enter — новая игра
+    fn game_over_key(&mut self, key: Key) {
+        if self.initials.is_some() {
+            match key {
+                Key::Backspace => self.erase_initial(),
+                Key::Return => self.confirm_initials(),
+                _ => {
+                    if let Some(c) = letter(key) {
+                        self.type_initial(c);
+                    }
+                }
+            }
+            return;
+        }
+        if self.speed_key(key) {
+            return;
+        }
+        if key == Key::Space || key == Key::Return {
+            self.restart();
+        }
+    }
+
+    // Клавиши скорости до забега и между забегами: 1–4 — уровень сложности, R — разгон,
+    // -/= — минимальный такт разгона. Во время забега скорость и цена еды не меняются.
+    fn speed_key(&mut self, key: Key) -> bool {
+        let preset = match key {
+            Key::D1 => Some(Difficulty::Easy),
+            Key::D2 => Some(Difficulty::Normal),
+            Key::D3 => Some(Difficulty::Hard),
+            Key::D4 => Some(Difficulty::Insane),
+            _ => None,
+        };
+        match (preset, key) {
+            (Some(difficulty), _) => self.set_difficulty(difficulty),
+            (None, Key::R) => self.set_speed_ramp(!self.speed_ramp, self.min_period),
+            (None, Key::Minus) => self.set_speed_ramp(self.speed_ramp, self.min_period - MIN_PERIOD_STEP),
+            (None, Key::Equals) => self.set_speed_ramp(self.speed_ramp, self.min_period + MIN_PERIOD_STEP),
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_changes_only_between_runs() {
+        let mut game = Game::with_seed(20, 20, 7);
+        game.key_pressed(Key::D4);
+        game.key_pressed(Key::R);
+        assert_eq!((game.difficulty, game.speed_ramp), (Difficulty::Normal, false));
+
+        game.is_game_over = true;
+        game.key_pressed(Key::D4);
+        game.key_pressed(Key::R);
+        assert_eq!((game.difficulty, game.speed_ramp), (Difficulty::Insane, true));
+
+        // Пока вводят инициалы, R — буква, а не разгон
+        game.initials = Some(String::new());
+        game.key_pressed(Key::R);
+        assert_eq!((game.initials.as_deref(), game.speed_ramp), (Some("R"), true));
+    }
+
+    #[test]
+    fn speed_is_chosen_before_the_first_run() {
+        let mut game = Game::with_seed(20, 20, 7);
+        game.is_ready = true;
+        let head = game.snake.head_position();
+        game.update(1.0);
+        assert_eq!(game.snake.head_position(), head);
+
+        game.key_pressed(Key::D1);
+        game.key_pressed(Key::R);
+        assert!(game.is_ready);
+        assert_eq!((game.difficulty, game.speed_ramp), (Difficulty::Easy, true));
+
+        // Стрелка начинает забег и сразу поворачивает змейку
+        game.key_pressed(Key::Down);
+        game.update(1.0);
+        assert!(!game.is_ready);
+        assert!(game.snake.head_direction() == Direction::Down);
+        game.key_pressed(Key::D4);
+        assert_eq!(game.difficulty, Difficulty::Easy);
+    }
+}
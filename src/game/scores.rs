@@ -0,0 +1,85 @@
+use crate::high_scores::{self, HighScore};
+
+use super::Game;
+
+impl Game {
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn length(&self) -> usize {
+        self.snake.positions().len()
+    }
+
+    // Конец игры: если счёт попал в таблицу рекордов, просим ввести инициалы.
+    // Забеги, где хоть раз вёл автопилот, и пошаговый режим рекорды не записывают.
+    pub(super) fn end_game(&mut self) {
+        self.is_game_over = true;
+        if self.record_scores && !self.autopilot_used && high_scores::qualifies(&self.high_scores, self.score()) {
+            self.initials = Some(String::new());
+        }
+    }
+
+    // Инициалы для рекорда: буквы по одной, стирание и подтверждение
+    pub fn type_initial(&mut self, letter: char) {
+        if let Some(initials) = self.initials.as_mut().filter(|initials| initials.len() < high_scores::INITIALS_LENGTH) {
+            initials.push(letter.to_ascii_uppercase());
+        }
+    }
+
+    pub fn erase_initial(&mut self) {
+        if let Some(initials) = self.initials.as_mut() {
+            initials.pop();
+        }
+    }
+
+    // Записывает рекорд с введёнными инициалами; пустые инициалы не принимаем
+    pub fn confirm_initials(&mut self) {
+        let initials = match self.initials.take() {
+            Some(initials) if !initials.is_empty() => initials,
+            other => {
+                self.initials = other;
+                return;
+            }
+        };
+        let entry = HighScore {
+            initials,
+            score: self.score(),
+            length: self.length(),
+            date: high_scores::today(),
+            difficulty: self.difficulty,
+        };
+        high_scores::insert(&mut self.high_scores, entry);
+        if let Err(err) = high_scores::save(&self.high_scores) {
+            eprintln!("Не удалось сохранить рекорды: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snake::Direction;
+
+    #[test]
+    fn autopilot_runs_are_not_recorded() {
+        let mut game = Game::with_seed(12, 12, 3);
+        game.record_scores = true;
+        game.toggle_autopilot();
+        for _ in 0..40 {
+            let direction = game.autopilot_direction();
+            game.step(direction);
+        }
+        assert!(game.score() > 0);
+        // Игрок забирает управление перед самой гибелью
+        game.toggle_autopilot();
+        while !game.step(Some(Direction::Up)).done {}
+        assert!(game.initials.is_none());
+
+        // Следующий забег без автопилота снова может попасть в таблицу
+        game.reset();
+        game.score = 5;
+        game.end_game();
+        assert!(game.initials.is_some());
+    }
+}
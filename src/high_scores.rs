@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app_dirs;
+use crate::game::Difficulty;
+
+const SCORES_FILE: &str = "snake_scores.txt";
+pub const TABLE_SIZE: usize = 10;
+pub const INITIALS_LENGTH: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+    pub length: usize,
+    pub date: String,
+    pub difficulty: Difficulty,
+}
+
+fn scores_path() -> io::Result<PathBuf> {
+    app_dirs::data_dir()
+        .map(|dir| dir.join(SCORES_FILE))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "не найдена папка данных пользователя"))
+}
+
+// Таблица рекордов, лучшие сверху. Нет файла или он повреждён — таблица пуста.
+pub fn load() -> Vec<HighScore> {
+    match scores_path().and_then(fs::read_to_string) {
+        Ok(text) => parse(&text),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn save(table: &[HighScore]) -> io::Result<()> {
+    let path = scores_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serialize(table))
+}
+
+// Формат строки: «инициалы;счёт;длина;дата;сложность». В старых строках сложности нет — считаем обычной.
+// Повреждённые строки пропускаем.
+fn parse(text: &str) -> Vec<HighScore> {
+    let mut table: Vec<HighScore> = text
+        .lines()
+        .filter_map(|line| {
+            let (initials, score, length, date, difficulty) = match *line.split(';').collect::<Vec<_>>().as_slice() {
+                [initials, score, length, date] => (initials, score, length, date, "Normal"),
+                [initials, score, length, date, difficulty] => (initials, score, length, date, difficulty),
+                _ => return None,
+            };
+            Some(HighScore {
+                initials: initials.to_owned(),
+                score: score.parse().ok()?,
+                length: length.parse().ok()?,
+                date: date.to_owned(),
+                difficulty: match difficulty {
+                    "Easy" => Difficulty::Easy,
+                    "Hard" => Difficulty::Hard,
+                    "Insane" => Difficulty::Insane,
+                    _ => Difficulty::Normal,
+                },
+            })
+        })
+        .collect();
+    table.sort_by_key(|entry| Reverse(entry.score));
+    table.truncate(TABLE_SIZE);
+    table
+}
+
+fn serialize(table: &[HighScore]) -> String {
+    let lines: Vec<String> = table
+        .iter()
+        .map(|entry| format!("{};{};{};{};{:?}", entry.initials, entry.score, entry.length, entry.date, entry.difficulty))
+        .collect();
+    lines.join("\n")
+}
+
+// Попадает ли счёт в таблицу
+pub fn qualifies(table: &[HighScore], score: u32) -> bool {
+    score > 0 && (table.len() < TABLE_SIZE || table.iter().any(|entry| score > entry.score))
+}
+
+// Вставляет рекорд на своё место; при равном счёте выше тот, кто раньше
+pub fn insert(table: &mut Vec<HighScore>, entry: HighScore) {
+    let place = table.iter().position(|other| entry.score > other.score).unwrap_or(table.len());
+    table.insert(place, entry);
+    table.truncate(TABLE_SIZE);
+}
+
+// Сегодняшняя дата ГГГГ-ММ-ДД по UTC, без внешних библиотек
+pub fn today() -> String {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    civil_date(days)
+}
+
+// Перевод числа дней с 1970-01-01 в дату григорианского календаря
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: u32, difficulty: Difficulty) -> HighScore {
+        HighScore { initials: initials.to_owned(), score, length: 3 + score as usize, date: "2024-02-29".to_owned(), difficulty }
+    }
+
+    #[test]
+    fn serialize_and_parse_round_trip() {
+        let table = vec![entry("ABC", 30, Difficulty::Insane), entry("XY", 12, Difficulty::Easy), entry("Q", 12, Difficulty::Normal)];
+        assert_eq!(parse(&serialize(&table)), table);
+    }
+
+    #[test]
+    fn old_lines_default_to_normal_and_broken_lines_are_skipped() {
+        let table = parse("ABC;10;8;2023-05-01\nпусто\nXYZ;много;3;2023-05-02\nQQQ;20;13;2023-05-03;Hard");
+        assert_eq!(table.len(), 2);
+        assert_eq!((table[0].score, table[0].difficulty), (20, Difficulty::Hard));
+        assert_eq!((table[1].score, table[1].difficulty), (10, Difficulty::Normal));
+    }
+
+    #[test]
+    fn table_keeps_top_scores_and_earlier_ties() {
+        let text: Vec<String> = (1..=12).map(|score| format!("P{};{};3;2024-01-01", score, score)).collect();
+        let table = parse(&text.join("\n"));
+        assert_eq!(table.len(), TABLE_SIZE);
+        assert_eq!((table[0].score, table[TABLE_SIZE - 1].score), (12, 3));
+
+        // Новый счёт, равный десятому месту, в таблицу не попадает: выше тот, кто раньше
+        let mut table = table;
+        assert!(!qualifies(&table, 3));
+        insert(&mut table, entry("NEW", 3, Difficulty::Normal));
+        assert_eq!(table.len(), TABLE_SIZE);
+        assert!(table.iter().all(|other| other.initials != "NEW"));
+
+        assert!(qualifies(&table, 4));
+        insert(&mut table, entry("NEW", 4, Difficulty::Normal));
+        assert_eq!(table[TABLE_SIZE - 2].initials, "P4");
+        assert_eq!(table[TABLE_SIZE - 1].initials, "NEW");
+    }
+
+    #[test]
+    fn civil_dates_with_leap_years() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(11_017), "2000-03-01");
+        assert_eq!(civil_date(19_782), "2024-02-29");
+        assert_eq!(civil_date(47_540), "2100-02-28");
+        assert_eq!(civil_date(47_541), "2100-03-01");
+        assert_eq!(civil_date(-1), "1969-12-31");
+    }
+}
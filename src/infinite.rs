@@ -0,0 +1,203 @@
+use eframe::egui;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng, seq::SliceRandom};
+use std::collections::HashMap;
+
+// Сторона чанка в клетках. Нулевые строка и столбец чанка — шов с соседями сверху и слева,
+// комнаты стоят на нечётных координатах, как и в обычном лабиринте
+const CHUNK: i64 = 16;
+// Чанки дальше этого расстояния от игрока выгружаются
+const KEEP_RADIUS: i64 = 6;
+
+type Chunk = Vec<Vec<bool>>;
+
+pub struct InfiniteMazeApp {
+    world_seed: u64,
+    chunks: HashMap<(i64, i64), Chunk>,
+    player: (i64, i64),
+    view_radius: i64,
+    show_seams: bool,
+}
+
+impl Default for InfiniteMazeApp {
+    fn default() -> Self {
+        InfiniteMazeApp {
+            world_seed: thread_rng().gen(),
+            chunks: HashMap::new(),
+            player: (1, 1),
+            view_radius: 20,
+            show_seams: false,
+        }
+    }
+}
+
+// Перемешивание координат чанка с зерном мира (splitmix64)
+fn chunk_seed(world_seed: u64, chunk: (i64, i64)) -> u64 {
+    let mut z = world_seed
+        ^ (chunk.0 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (chunk.1 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Чанк зависит только от зерна мира и своих координат. Внутри — совершенный лабиринт,
+// а в своих швах (левом и верхнем) чанк пробивает проходы к комнатам соседей.
+// Каждым швом владеет ровно один чанк, поэтому соседям не нужно договариваться.
+fn generate_chunk(world_seed: u64, chunk: (i64, i64)) -> Chunk {
+    let size = CHUNK as usize;
+    let mut rng = StdRng::seed_from_u64(chunk_seed(world_seed, chunk));
+    let mut grid = vec![vec![true; size]; size];
+
+    grid[1][1] = false;
+    let mut stack: Vec<(usize, usize)> = vec![(1, 1)];
+    while let Some((row, col)) = stack.pop() {
+        let mut neighbors = vec![
+            (row.wrapping_sub(2), col, row.wrapping_sub(1), col),
+            (row + 2, col, row + 1, col),
+            (row, col.wrapping_sub(2), row, col.wrapping_sub(1)),
+            (row, col + 2, row, col + 1),
+        ];
+        neighbors.shuffle(&mut rng);
+
+        for (nr, nc, wr, wc) in neighbors {
+            if nr > 0 && nr < size && nc > 0 && nc < size && grid[nr][nc] {
+                grid[nr][nc] = false;
+                grid[wr][wc] = false;
+                stack.push((nr, nc));
+            }
+        }
+    }
+
+    let odd: Vec<usize> = (1..size).step_by(2).collect();
+    for _ in 0..rng.gen_range(1..=2) {
+        grid[*odd.choose(&mut rng).unwrap()][0] = false;
+        grid[0][*odd.choose(&mut rng).unwrap()] = false;
+    }
+    grid
+}
+
+fn chunk_of((x, y): (i64, i64)) -> (i64, i64) {
+    (x.div_euclid(CHUNK), y.div_euclid(CHUNK))
+}
+
+impl InfiniteMazeApp {
+    fn ensure_chunks(&mut self, from: (i64, i64), to: (i64, i64)) {
+        let (min_cx, min_cy) = chunk_of(from);
+        let (max_cx, max_cy) = chunk_of(to);
+        for cx in min_cx..=max_cx {
+            for cy in min_cy..=max_cy {
+                let seed = self.world_seed;
+                self.chunks.entry((cx, cy)).or_insert_with(|| generate_chunk(seed, (cx, cy)));
+            }
+        }
+
+        let (px, py) = chunk_of(self.player);
+        self.chunks.retain(|&(cx, cy), _| (cx - px).abs() <= KEEP_RADIUS && (cy - py).abs() <= KEEP_RADIUS);
+    }
+
+    // Клетки вне загруженных чанков считаются стенами
+    fn is_wall(&self, (x, y): (i64, i64)) -> bool {
+        match self.chunks.get(&chunk_of((x, y))) {
+            Some(chunk) => chunk[y.rem_euclid(CHUNK) as usize][x.rem_euclid(CHUNK) as usize],
+            None => true,
+        }
+    }
+
+    fn try_move(&mut self, dx: i64, dy: i64) {
+        let target = (self.player.0 + dx, self.player.1 + dy);
+        self.ensure_chunks(target, target);
+        if !self.is_wall(target) {
+            self.player = target;
+        }
+    }
+
+    fn new_world(&mut self, seed: u64) {
+        self.world_seed = seed;
+        self.chunks.clear();
+        self.player = (1, 1);
+    }
+}
+
+impl eframe::App for InfiniteMazeApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let moves = [
+            (egui::Key::ArrowUp, egui::Key::W, (0, -1)),
+            (egui::Key::ArrowDown, egui::Key::S, (0, 1)),
+            (egui::Key::ArrowLeft, egui::Key::A, (-1, 0)),
+            (egui::Key::ArrowRight, egui::Key::D, (1, 0)),
+        ];
+        for (arrow, letter, (dx, dy)) in moves {
+            if ctx.input(|i| i.key_pressed(arrow) || i.key_pressed(letter)) {
+                self.try_move(dx, dy);
+            }
+        }
+
+        egui::SidePanel::left("infinite_panel")
+            .resizable(false)
+            .default_width(200.0)
+            .show(ctx, |ui| {
+                ui.label("Стрелки или WASD — ходить");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Зерно мира:");
+                    let mut seed = self.world_seed;
+                    if ui.add(egui::DragValue::new(&mut seed)).changed() {
+                        self.new_world(seed);
+                    }
+                });
+                if ui.button("Новый мир").clicked() {
+                    self.new_world(thread_rng().gen());
+                }
+
+                ui.label("Обзор:");
+                ui.add(egui::Slider::new(&mut self.view_radius, 5..=60));
+                ui.checkbox(&mut self.show_seams, "Показать границы чанков");
+
+                ui.separator();
+                ui.label(format!("Позиция: {}, {}", self.player.0, self.player.1));
+                let (cx, cy) = chunk_of(self.player);
+                ui.label(format!("Чанк: {}, {}", cx, cy));
+                ui.label(format!("Загружено чанков: {}", self.chunks.len()));
+            });
+
+        let radius = self.view_radius;
+        let (px, py) = self.player;
+        self.ensure_chunks((px - radius, py - radius), (px + radius, py + radius));
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let available_size = ui.available_size();
+            let (response, painter) = ui.allocate_painter(available_size, egui::Sense::hover());
+            let span = (radius * 2 + 1) as f32;
+            let cell_size = (available_size.x.min(available_size.y) / span).max(1.0);
+            let origin = response.rect.center() - egui::vec2(cell_size, cell_size) * span / 2.0;
+            let cell_rect = |x: i64, y: i64| {
+                egui::Rect::from_min_size(
+                    origin + egui::vec2((x - px + radius) as f32, (y - py + radius) as f32) * cell_size,
+                    egui::vec2(cell_size, cell_size),
+                )
+            };
+
+            for y in py - radius..=py + radius {
+                for x in px - radius..=px + radius {
+                    let color = if self.is_wall((x, y)) { egui::Color32::WHITE } else { egui::Color32::BLACK };
+                    painter.rect_filled(cell_rect(x, y), 0.0, color);
+                }
+            }
+
+            if self.show_seams {
+                let seam = egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(255, 0, 0, 120));
+                for x in (px - radius..=px + radius).filter(|x| x.rem_euclid(CHUNK) == 0) {
+                    let top = cell_rect(x, py - radius).left_top();
+                    painter.line_segment([top, top + egui::vec2(0.0, span * cell_size)], seam);
+                }
+                for y in (py - radius..=py + radius).filter(|y| y.rem_euclid(CHUNK) == 0) {
+                    let left = cell_rect(px - radius, y).left_top();
+                    painter.line_segment([left, left + egui::vec2(span * cell_size, 0.0)], seam);
+                }
+            }
+
+            painter.circle_filled(cell_rect(px, py).center(), cell_size * 0.4, egui::Color32::GREEN);
+        });
+    }
+}
@@ -0,0 +1,10 @@
+// Общий код обеих программ: змейка, рекорды и поиск пути.
+// Змейкой можно управлять и без окна — через step и observation. Рисование и клавиатура
+// piston включаются возможностью gui; без неё библиотека не тянет за собой графику.
+pub mod app_dirs;
+#[cfg(feature = "gui")]
+pub mod drawing;
+pub mod game;
+pub mod high_scores;
+pub mod pathfinding;
+pub mod snake;
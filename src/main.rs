@@ -5,20 +5,23 @@ use std::process::Command;
 extern crate piston_window;
 extern crate rand;
 const BACK_COLOR: Color = [0.204, 0.286, 0.369, 1.0];
-use drawing::to_gui_coord_u32;
+use dijkstra_maze::drawing::to_gui_coord_u32;
+use dijkstra_maze::{app_dirs, game, high_scores, pathfinding};
+mod infinite;
 mod maze;
-mod game; 
-mod drawing; 
-mod snake;
 enum AppState {
     Menu,
     MazeGame(maze::MazeApp),
+    InfiniteMaze(infinite::InfiniteMazeApp),
     SnakeGame,
 }
 
 pub struct MyApp {
     state: AppState,
     background: Option<TextureHandle>, 
+    // Змейка — отдельная программа, поэтому таблицу рекордов время от времени перечитываем
+    high_scores: Vec<high_scores::HighScore>,
+    scores_loaded_at: f64,
 }
 
 impl Default for MyApp {
@@ -26,6 +29,8 @@ impl Default for MyApp {
         MyApp {
             state: AppState::Menu,
             background: None,
+            high_scores: Vec::new(),
+            scores_loaded_at: f64::NEG_INFINITY,
         }
     }
 }
@@ -41,6 +46,7 @@ impl eframe::App for MyApp {
         match &mut self.state {
             AppState::Menu => self.show_menu(ctx),
             AppState::MazeGame(app) => app.update(ctx, frame),
+            AppState::InfiniteMaze(app) => app.update(ctx, frame),
             AppState::SnakeGame => {
                 
                 run_snake_game();
@@ -48,6 +54,13 @@ impl eframe::App for MyApp {
             }
         }
     }
+
+    fn on_close_event(&mut self) -> bool {
+        if let AppState::MazeGame(app) = &self.state {
+            app.save();
+        }
+        true
+    }
 }
 
 fn run_snake_game() {
@@ -60,6 +73,8 @@ fn run_snake_game() {
     window_settings.set_vsync(true);
     let mut window: PistonWindow = window_settings.build().unwrap();
     let mut game = game::Game::new(width, height);
+    // Здесь нет строки состояния с выбором скорости, поэтому забег начинается сразу
+    game.start();
 
     while let Some(event) = window.next() {
         if let Some(Button::Keyboard(key)) = event.press_args() {
@@ -77,6 +92,12 @@ fn run_snake_game() {
 
 impl MyApp {
     fn show_menu(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        if now - self.scores_loaded_at > 2.0 {
+            self.high_scores = high_scores::load();
+            self.scores_loaded_at = now;
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(background) = &self.background {
                 let available_rect = ui.available_rect_before_wrap();
@@ -89,7 +110,7 @@ impl MyApp {
             }
 
             let menu_width = 300.0;
-            let menu_height = 300.0;
+            let menu_height = 640.0;
             let centered_rect = egui::Rect::from_center_size(
                 ui.available_rect_before_wrap().center(),
                 egui::vec2(menu_width, menu_height),
@@ -113,7 +134,21 @@ impl MyApp {
                     .stroke(egui::Stroke::new(2.0, egui::Color32::BLACK));
 
                     if ui.add(maze_button).clicked() {
-                        self.state = AppState::MazeGame(maze::MazeApp::default());
+                        self.state = AppState::MazeGame(maze::MazeApp::restored());
+                    }
+
+                    ui.add_space(20.0);
+
+                    let infinite_button = egui::Button::new(
+                        egui::RichText::new("Бесконечный лабиринт")
+                            .font(egui::FontId::new(24.0, egui::FontFamily::Proportional)),
+                    )
+                    .min_size(egui::vec2(200.0, 60.0))
+                    .fill(egui::Color32::DARK_GRAY)
+                    .stroke(egui::Stroke::new(2.0, egui::Color32::BLACK));
+
+                    if ui.add(infinite_button).clicked() {
+                        self.state = AppState::InfiniteMaze(infinite::InfiniteMazeApp::default());
                     }
 
                     ui.add_space(20.0);
@@ -131,6 +166,27 @@ impl MyApp {
                         .spawn()
                         .expect("Не удалось запустить игру Змейка");
                     }
+
+                    ui.add_space(20.0);
+                    ui.label(
+                        egui::RichText::new("Рекорды змейки")
+                            .font(egui::FontId::new(20.0, egui::FontFamily::Proportional))
+                            .color(egui::Color32::YELLOW),
+                    );
+                    if self.high_scores.is_empty() {
+                        ui.label("Пока пусто");
+                    }
+                    egui::Grid::new("high_scores").striped(true).show(ui, |ui| {
+                        for (place, entry) in self.high_scores.iter().enumerate() {
+                            ui.label(format!("{}.", place + 1));
+                            ui.label(&entry.initials);
+                            ui.label(entry.score.to_string());
+                            ui.label(format!("длина {}", entry.length));
+                            ui.label(entry.difficulty.name());
+                            ui.label(&entry.date);
+                            ui.end_row();
+                        }
+                    });
                 });
             });
         });
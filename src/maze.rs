@@ -1,35 +1,118 @@
 use eframe::egui;
-use rand::{thread_rng, Rng, seq::SliceRandom};
-use std::collections::{BinaryHeap, HashMap};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng, seq::SliceRandom};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::pathfinding::{self, Graph};
+
+mod agents;
+mod directions;
+mod history;
+mod keys;
+mod movement;
+mod one_way;
+mod portals;
+mod print;
+mod settings;
+mod shortest;
+mod timed;
+mod topology;
+mod wall_break;
+mod weave;
+mod yen;
+
+const MAX_ATTEMPTS: usize = 20;
+// Допустимые размеры лабиринта: ползунок и сохранённые настройки
+const GRID_SIZES: std::ops::RangeInclusive<usize> = 5..=251;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Node {
-    row: usize,
-    col: usize,
-    cost: usize,
+pub enum GenerateError {
+    EmptyMask,
+    MaskTooSmall,
+    Unsolvable { attempts: usize },
 }
 
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::EmptyMask => write!(f, "Маска пуста: нет тёмных пикселей"),
+            GenerateError::MaskTooSmall => write!(f, "Маска слишком мала для лабиринта такого размера"),
+            GenerateError::Unsolvable { attempts } => {
+                write!(f, "Не удалось построить проходимый лабиринт за {} попыток", attempts)
+            }
+        }
     }
 }
 
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+// Сообщение в боковой панели: ошибки показываются красным
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Empty,
+    Info(String),
+    Error(String),
 }
 
 pub struct MyApp {
     grid: Vec<Vec<bool>>,        
     path: Vec<(usize, usize)>,   
     grid_size: usize,
+    seed: u64,
     path_color: egui::Color32,
     wall_color: egui::Color32,
     passage_color: egui::Color32,
-    entrance_row: usize,
-    exit_row: usize,
+    entrance: (usize, usize),
+    exit: (usize, usize),
+    mask_path: String,
+    mask_image: Option<image::GrayImage>,
+    mask: Option<Vec<Vec<bool>>>,
+    image_path: String,
+    source_image: Option<image::RgbaImage>,
+    source_texture: Option<egui::TextureHandle>,
+    image_threshold: u8,
+    image_resolution: usize,
+    picking_exit: bool,
+    door_count: usize,
+    doors: HashMap<(usize, usize), usize>,
+    keys: HashMap<(usize, usize), usize>,
+    key_order: Vec<usize>,
+    gate_count: usize,
+    hazard_count: usize,
+    gates: Vec<timed::Gate>,
+    hazards: Vec<timed::Hazard>,
+    connectivity: movement::Connectivity,
+    topology: topology::Topology,
+    open_grid: bool,
+    obstacle_percent: usize,
+    one_way_percent: usize,
+    one_way: HashMap<(usize, usize), (isize, isize)>,
+    weave_percent: usize,
+    crossings: HashMap<(usize, usize), (isize, isize)>,
+    portal_count: usize,
+    portal_cost: usize,
+    portals: Vec<((usize, usize), (usize, usize))>,
+    agent_count: usize,
+    agents: Vec<agents::Agent>,
+    agent_paths: Vec<Vec<(usize, usize)>>,
+    animation_start: Option<f64>,
+    history: history::History,
+    edit_mode: bool,
+    print_count: usize,
+    print_format: print::PageFormat,
+    print_folder: String,
+    braid_percent: usize,
+    shortest_paths: Vec<Vec<(usize, usize)>>,
+    shortest_count: u128,
+    shortest_index: usize,
+    path_usage: HashMap<(usize, usize), u128>,
+    show_heatmap: bool,
+    route_count: usize,
+    routes: Vec<(Vec<(usize, usize)>, f64)>,
+    wall_budget: usize,
+    broken_walls: Vec<(usize, usize)>,
+    break_lengths: Vec<Option<usize>>,
+    directions_file: String,
+    hovered_direction: Option<(usize, usize)>,
+    status: Status,
 }
 
 impl Default for MyApp {
@@ -40,125 +123,494 @@ impl Default for MyApp {
             grid,
             path: Vec::new(),
             grid_size,
+            seed: 0,
             path_color: egui::Color32::GREEN,
             wall_color: egui::Color32::WHITE,
             passage_color: egui::Color32::BLACK,
-            entrance_row: 1,       
-            exit_row: grid_size - 2 
+            entrance: (1, 0),
+            exit: (grid_size - 2, grid_size - 1),
+            mask_path: String::new(),
+            mask_image: None,
+            mask: None,
+            image_path: String::new(),
+            source_image: None,
+            source_texture: None,
+            image_threshold: 128,
+            image_resolution: 101,
+            picking_exit: false,
+            door_count: 0,
+            doors: HashMap::new(),
+            keys: HashMap::new(),
+            key_order: Vec::new(),
+            gate_count: 0,
+            hazard_count: 0,
+            gates: Vec::new(),
+            hazards: Vec::new(),
+            connectivity: movement::Connectivity::Four,
+            topology: topology::Topology::Plane,
+            open_grid: false,
+            obstacle_percent: 25,
+            one_way_percent: 0,
+            one_way: HashMap::new(),
+            weave_percent: 0,
+            crossings: HashMap::new(),
+            portal_count: 0,
+            portal_cost: 1,
+            portals: Vec::new(),
+            agent_count: 3,
+            agents: Vec::new(),
+            agent_paths: Vec::new(),
+            animation_start: None,
+            history: history::History::default(),
+            edit_mode: false,
+            print_count: 12,
+            print_format: print::PageFormat::A4,
+            print_folder: String::from("."),
+            braid_percent: 0,
+            shortest_paths: Vec::new(),
+            shortest_count: 0,
+            shortest_index: 0,
+            path_usage: HashMap::new(),
+            show_heatmap: false,
+            route_count: 3,
+            routes: Vec::new(),
+            wall_budget: 1,
+            broken_walls: Vec::new(),
+            break_lengths: Vec::new(),
+            directions_file: String::from("directions.txt"),
+            hovered_direction: None,
+            status: Status::Empty,
         }
     }
 }
 
 impl MyApp {
-    fn generate_maze(&mut self) {
-        loop {
-            let rows = self.grid_size;
-            let cols = self.grid_size;
-    
+    // Генерирует лабиринт по текущему зерну. Проходимость обеспечивается построением:
+    // вход и выход всегда примыкают к вырезанным клеткам. Повторные попытки нужны только
+    // открытой карте и дополнениям (двери, ворота), их число ограничено MAX_ATTEMPTS.
+    // При ошибке остаётся прежний лабиринт.
+    fn generate_maze(&mut self) -> Result<(), GenerateError> {
+        let mask = self.mask_grid();
+        if let Some(mask) = &mask {
+            if !mask.iter().flatten().any(|&inside| inside) {
+                return Err(GenerateError::EmptyMask);
+            }
+        }
+
+        let rows = self.grid_size;
+        let cols = self.grid_size;
+        let inside = |row: usize, col: usize| mask.as_ref().is_none_or(|m| m[row][col]);
+
+        // С маской стартуем с первой нечётной клетки внутри фигуры
+        let start = (1..rows - 1).step_by(2)
+            .flat_map(|row| (1..cols - 1).step_by(2).map(move |col| (row, col)))
+            .find(|&(row, col)| inside(row, col))
+            .ok_or(GenerateError::MaskTooSmall)?;
+
+        let previous = self.snapshot();
+        self.source_image = None;
+        self.source_texture = None;
+        self.mask = mask.clone();
+
+        // Один генератор на все попытки: по зерну лабиринт воспроизводится полностью
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for _ in 0..MAX_ATTEMPTS {
             self.grid = vec![vec![true; cols]; rows];
+            self.crossings.clear();
+
+            if self.open_grid {
+                self.carve_open_grid(&mut rng, &inside);
+            } else {
+                let (start_row, start_col) = start;
+                self.grid[start_row][start_col] = false; 
+                let mut stack = Vec::new();
+                stack.push((start_row, start_col));
     
-            let mut rng = thread_rng();
-            self.entrance_row = rng.gen_range(1..rows - 1);
-            self.exit_row = rng.gen_range(1..rows - 1);
-    
-            self.grid[self.entrance_row][0] = false; 
-            self.grid[self.exit_row][cols - 1] = false;
-    
-            let start_row = 1;
-            let start_col = 1;
-            self.grid[start_row][start_col] = false; 
-            let mut stack = Vec::new();
-            stack.push((start_row, start_col));
-    
-            while let Some((row, col)) = stack.pop() {
-                let mut neighbors = vec![
-                    (row.wrapping_sub(2), col, row.wrapping_sub(1), col), 
-                    (row + 2, col, row + 1, col), 
-                    (row, col.wrapping_sub(2), row, col.wrapping_sub(1)), 
-                    (row, col + 2, row, col + 1), 
-                ];
-                neighbors.shuffle(&mut rng);
+                while let Some(room) = stack.pop() {
+                    let mut directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+                    directions.shuffle(&mut rng);
     
-                for (nr, nc, wr, wc) in neighbors {
-                    if nr > 0 && nr < rows && nc > 0 && nc < cols && self.grid[nr][nc] {
-                        self.grid[nr][nc] = false; 
-                        self.grid[wr][wc] = false; 
-                        stack.push((nr, nc));
+                    // Через шов склеенных краёв соседняя комната лежит за двумя стенами
+                    for direction in directions {
+                        let Some((walls, (nr, nc))) = self.room_beyond(room, direction) else {
+                            continue;
+                        };
+                        if self.grid[nr][nc] && inside(nr, nc) && walls.iter().all(|&(wr, wc)| inside(wr, wc)) {
+                            self.grid[nr][nc] = false; 
+                            for (wr, wc) in walls {
+                                self.grid[wr][wc] = false; 
+                            }
+                            stack.push((nr, nc));
+                        }
                     }
                 }
+                self.weave(&mut rng, &inside);
+                self.braid(&mut rng);
             }
-    
 
-            let path = self.dijkstra((self.entrance_row, 0), (self.exit_row, self.grid_size - 1));
-            if path.is_empty() {
+            if mask.is_some() {
+                // Вход и выход — самые левая и самая правая вырезанные клетки фигуры
+                let carved: Vec<(usize, usize)> = (0..rows)
+                    .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                    .filter(|&(row, col)| !self.grid[row][col])
+                    .collect();
+                if carved.is_empty() {
+                    continue;
+                }
+                self.entrance = *carved.iter().min_by_key(|&&(row, col)| (col, row)).unwrap();
+                self.exit = *carved.iter().max_by_key(|&&(row, col)| (col, rows - row)).unwrap();
+            } else {
+                match self.topology {
+                    topology::Topology::Plane => {
+                        // Нечётные строки: рядом со входом и выходом всегда есть комната
+                        self.entrance = (rng.gen_range(0..(rows - 1) / 2) * 2 + 1, 0);
+                        self.exit = (rng.gen_range(0..(rows - 1) / 2) * 2 + 1, cols - 1);
+                        self.grid[self.entrance.0][self.entrance.1] = false; 
+                        self.grid[self.exit.0][self.exit.1] = false;
+                        if self.open_grid {
+                            self.grid[self.entrance.0][1] = false;
+                            self.grid[self.exit.0][cols - 2] = false;
+                        }
+                    }
+                    topology::Topology::Cylinder => {
+                        // Левый и правый край склеены, поэтому вход сверху, а выход снизу
+                        self.entrance = (0, rng.gen_range(0..(cols - 1) / 2) * 2 + 1);
+                        self.exit = (rows - 1, rng.gen_range(0..(cols - 1) / 2) * 2 + 1);
+                        self.grid[self.entrance.0][self.entrance.1] = false; 
+                        self.grid[self.exit.0][self.exit.1] = false;
+                        if self.open_grid {
+                            self.grid[1][self.entrance.1] = false;
+                            self.grid[rows - 2][self.exit.1] = false;
+                        }
+                    }
+                    topology::Topology::Torus => {
+                        // У тора нет края: вход в случайной комнате, выход в самой далёкой от него
+                        self.entrance = (rng.gen_range(0..(rows - 1) / 2) * 2 + 1, rng.gen_range(0..(cols - 1) / 2) * 2 + 1);
+                        self.grid[self.entrance.0][self.entrance.1] = false;
+                        self.exit = self.farthest_cell(self.entrance);
+                        if self.exit == self.entrance {
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            if self.dijkstra(self.entrance, self.exit).is_empty() {
                 continue;
             }
 
-            let entrance_cell = (self.entrance_row, 0);
-            let exit_cell = (self.exit_row, self.grid_size - 1);
-            let valid_path = path.first() == Some(&entrance_cell) && path.last() == Some(&exit_cell);
-            if !valid_path {
-                continue; 
+            self.place_portals(&mut rng);
+            self.place_one_way(&mut rng);
+            let doors = self.place_keys_and_doors(&mut rng);
+            self.place_gates_and_hazards(&mut rng);
+            self.agents.clear();
+            self.agent_paths.clear();
+            self.animation_start = None;
+            self.solve_maze();
+            if self.path.is_empty() {
+                continue;
             }
-    
-            break; 
+            self.path.clear(); 
+            self.key_order.clear();
+            self.clear_overlays();
+            self.status = if doors < self.door_count {
+                Status::Info(format!("Дверей поставлено {} из {}: остальные не перекрыли бы путь к выходу", doors, self.door_count))
+            } else {
+                Status::Empty
+            };
+            return Ok(());
         }
-    
-        self.path.clear(); 
+
+        self.restore(previous);
+        Err(GenerateError::Unsolvable { attempts: MAX_ATTEMPTS })
     }
 
-    fn solve_maze(&mut self) {
-        let start = (self.entrance_row, 0);
-        let end = (self.exit_row, self.grid_size - 1); 
-        self.path = self.dijkstra(start, end);
+    // Генерация для кнопок и ползунков: ошибку показываем в боковой панели
+    fn regenerate(&mut self) {
+        if let Err(err) = self.generate_maze() {
+            self.status = Status::Error(err.to_string());
+        }
+    }
 
+    fn load_mask(&mut self) {
+        match image::open(self.mask_path.trim()) {
+            Ok(image) => {
+                self.mask_image = Some(image.into_luma8());
+                self.regenerate();
+            }
+            Err(err) => self.status = Status::Error(format!("Не удалось загрузить маску: {}", err)),
+        }
+    }
 
-        if self.path.is_empty() {
-            println!("Путь не найден! Возможно, лабиринт некорректен.");
+    fn open_image(&mut self) {
+        match image::open(self.image_path.trim()) {
+            Ok(image) => {
+                self.source_image = Some(image.into_rgba8());
+                self.source_texture = None;
+                self.mask = None;
+                self.threshold_image();
+                self.status = Status::Info("Кликните по входу, затем по выходу".to_owned());
+            }
+            Err(err) => self.status = Status::Error(format!("Не удалось открыть изображение: {}", err)),
         }
     }
 
-    fn dijkstra(&self, start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
-        let mut heap = BinaryHeap::new();
-        let mut distances: HashMap<(usize, usize), usize> = HashMap::new();
-        let mut came_from: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
-        let (start_row, start_col) = start;
-        distances.insert(start, 0);
-        heap.push(Node { row: start_row, col: start_col, cost: 0 });
+    // Переводит изображение в сетку: тёмные пиксели становятся стенами
+    fn threshold_image(&mut self) {
+        let image = match &self.source_image {
+            Some(image) => image,
+            None => return,
+        };
+        let cols = self.image_resolution.min(image.width() as usize).max(2);
+        let rows = ((cols as f32 * image.height() as f32 / image.width() as f32).round() as usize).max(2);
+        let luma = image::DynamicImage::ImageRgba8(image.clone()).into_luma8();
+        let scaled = image::imageops::resize(&luma, cols as u32, rows as u32, image::imageops::FilterType::Triangle);
 
-        while let Some(Node { row, col, cost }) = heap.pop() {
-            if (row, col) == end {
-                break; 
-            }
+        self.grid = (0..rows)
+            .map(|row| (0..cols).map(|col| scaled.get_pixel(col as u32, row as u32)[0] < self.image_threshold).collect())
+            .collect();
+        self.path.clear();
+        self.doors.clear();
+        self.keys.clear();
+        self.key_order.clear();
+        self.gates.clear();
+        self.hazards.clear();
+        self.portals.clear();
+        self.one_way.clear();
+        self.crossings.clear();
+        self.agents.clear();
+        self.agent_paths.clear();
+        self.clear_overlays();
+        self.picking_exit = false;
+    }
 
-            let neighbors = [
-                (row.wrapping_sub(1), col), 
-                (row + 1, col),            
-                (row, col.wrapping_sub(1)), 
-                (row, col + 1),          
-            ];
+    // Убирает всё, что нарисовано поверх сетки по прошлым расчётам: кратчайшие пути и
+    // тепловую карту, K маршрутов и сломанные стены
+    fn clear_overlays(&mut self) {
+        self.clear_shortest_paths();
+        self.routes.clear();
+        self.broken_walls.clear();
+        self.break_lengths.clear();
+    }
 
-            for &(n_row, n_col) in &neighbors {
-                if n_row < self.grid.len() && n_col < self.grid[0].len() && !self.grid[n_row][n_col] {
-                    let next_cost = cost + 1;
-                    if next_cost < *distances.get(&(n_row, n_col)).unwrap_or(&usize::MAX) {
-                        distances.insert((n_row, n_col), next_cost);
-                        came_from.insert((n_row, n_col), (row, col));
-                        heap.push(Node { row: n_row, col: n_col, cost: next_cost });
-                    }
+    fn pick_endpoint(&mut self, cell: (usize, usize)) {
+        if self.grid[cell.0][cell.1] {
+            self.status = Status::Error("Это стена — выберите клетку прохода".to_owned());
+            return;
+        }
+
+        if self.picking_exit {
+            self.exit = cell;
+            self.picking_exit = false;
+            self.solve_maze();
+            self.status = if self.path.is_empty() {
+                Status::Error("Путь между выбранными точками не найден".to_owned())
+            } else {
+                Status::Info(format!("Длина пути: {:.1}", self.path_length(&self.path)))
+            };
+        } else {
+            self.entrance = cell;
+            self.path.clear();
+            self.picking_exit = true;
+            self.status = Status::Info("Теперь кликните по выходу".to_owned());
+        }
+    }
+
+    // Рисует найденный путь поверх исходного изображения и сохраняет рядом с ним
+    fn export_solution(&mut self) {
+        let mut image = match &self.source_image {
+            Some(image) => image.clone(),
+            None => return,
+        };
+        let rows = self.grid.len() as u32;
+        let cols = self.grid[0].len() as u32;
+        let color = self.path_color;
+
+        for &(row, col) in &self.path {
+            let (x0, x1) = (col as u32 * image.width() / cols, (col as u32 + 1) * image.width() / cols);
+            let (y0, y1) = (row as u32 * image.height() / rows, (row as u32 + 1) * image.height() / rows);
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    image.put_pixel(x, y, image::Rgba([color.r(), color.g(), color.b(), 255]));
                 }
             }
         }
 
-        let mut current = end;
-        let mut path = Vec::new();
-        while let Some(&prev) = came_from.get(&current) {
-            path.push(current);
-            current = prev;
+        let source = std::path::Path::new(self.image_path.trim());
+        let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("maze");
+        let target = source.with_file_name(format!("{}_solved.png", stem));
+        self.status = match image.save(&target) {
+            Ok(()) => Status::Info(format!("Сохранено: {}", target.display())),
+            Err(err) => Status::Error(format!("Не удалось сохранить: {}", err)),
+        };
+    }
+
+    // Маска, приведённая к размеру сетки: true — клетка внутри фигуры (тёмный пиксель)
+    fn mask_grid(&self) -> Option<Vec<Vec<bool>>> {
+        let image = self.mask_image.as_ref()?;
+        let size = self.grid_size as u32;
+        // Вписываем картинку в сетку с сохранением пропорций, поля по краям — снаружи фигуры
+        let longest = image.width().max(image.height()).max(1);
+        let width = (image.width() * size / longest).max(1);
+        let height = (image.height() * size / longest).max(1);
+        let scaled = image::imageops::resize(image, width, height, image::imageops::FilterType::Triangle);
+        let (left, top) = ((size - width) / 2, (size - height) / 2);
+        let inside = |row: u32, col: u32| {
+            (top..top + height).contains(&row)
+                && (left..left + width).contains(&col)
+                && scaled.get_pixel(col - left, row - top)[0] < 128
+        };
+        Some((0..size).map(|row| (0..size).map(|col| inside(row, col)).collect()).collect())
+    }
+
+    // Стены снаружи маски не рисуем, кроме тех, что очерчивают контур фигуры
+    fn is_visible_wall(&self, mask: &[Vec<bool>], row: usize, col: usize) -> bool {
+        if mask[row][col] {
+            return true;
         }
-        path.push(start);
-        path.reverse();
-        path
+        (row.saturating_sub(1)..=(row + 1).min(self.grid.len() - 1)).any(|r| {
+            (col.saturating_sub(1)..=(col + 1).min(self.grid[0].len() - 1)).any(|c| !self.grid[r][c])
+        })
+    }
+
+    fn solve_maze(&mut self) {
+        if !self.gates.is_empty() || !self.hazards.is_empty() {
+            let (path, key_order) = self.solve_timed().unwrap_or_default();
+            self.path = path;
+            self.key_order = key_order;
+        } else if self.doors.is_empty() {
+            self.path = self.dijkstra(self.entrance, self.exit);
+        } else {
+            let (path, key_order) = self.solve_with_keys().unwrap_or_default();
+            self.path = path;
+            self.key_order = key_order;
+        }
+
+
+        if self.path.is_empty() {
+            self.status = Status::Error("Путь не найден: выход недостижим".to_owned());
+        }
+    }
+
+    // Соседние клетки прохода без учёта направления коридоров, включая выход из туннеля под мостом
+    fn adjacent(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut adjacent: Vec<(usize, usize)> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(|direction| self.step(cell, direction))
+            .filter(|&(n_row, n_col)| !self.grid[n_row][n_col] && !self.crosses_bridge(cell, (n_row, n_col)))
+            .collect();
+        adjacent.extend(self.under_passages(cell));
+        adjacent
+    }
+
+    // Соседние клетки прохода, в которые можно шагнуть из cell
+    fn neighbors(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        self.adjacent(cell).into_iter().filter(|&next| self.allows_move(cell, next)).collect()
+    }
+
+    // Рёбра графа со стоимостью: соседние клетки, туннели под мостами (две клетки),
+    // диагонали и парный портал
+    fn edges(&self, cell: (usize, usize)) -> Vec<((usize, usize), f64)> {
+        let mut edges: Vec<((usize, usize), f64)> = self
+            .neighbors(cell)
+            .into_iter()
+            .map(|next| (next, if self.is_under_passage(cell, next) { 2.0 } else { 1.0 }))
+            .collect();
+        edges.extend(self.diagonal_edges(cell));
+        if let Some(partner) = self.portal_partner(cell) {
+            edges.push((partner, self.portal_cost as f64));
+        }
+        edges
+    }
+
+    // Стоимость пути с учётом диагоналей и порталов
+    fn path_length(&self, path: &[(usize, usize)]) -> f64 {
+        path.windows(2)
+            .map(|step| {
+                self.edges(step[0])
+                    .into_iter()
+                    .find(|&(next, _)| next == step[1])
+                    .map_or(1.0, |(_, cost)| cost)
+            })
+            .sum()
+    }
+
+    fn dijkstra(&self, start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
+        self.dijkstra_avoiding(start, end, &HashSet::new(), &HashSet::new())
+    }
+
+    // Дейкстра, которой запрещено заходить в banned_nodes и проходить по banned_edges
+    fn dijkstra_avoiding(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+        banned_nodes: &HashSet<(usize, usize)>,
+        banned_edges: &HashSet<Edge>,
+    ) -> Vec<(usize, usize)> {
+        let graph = MazeGraph { maze: self, banned_nodes, banned_edges };
+        pathfinding::dijkstra(&graph, start, |cell| cell == end).map_or_else(Vec::new, |(path, _)| path)
+    }
+
+    // Число шагов по соседним клеткам от start до end, без диагоналей и порталов
+    fn step_distance(&self, start: (usize, usize), end: (usize, usize)) -> Option<usize> {
+        let graph = StepGraph { maze: self, blocked: &[], moves: Moves::Forward };
+        pathfinding::bfs(&graph, start, |cell| cell == end).map(|path| path.len() - 1)
+    }
+}
+
+// Переход из клетки в клетку
+type Edge = ((usize, usize), (usize, usize));
+
+// Лабиринт как граф для общего поиска пути
+struct MazeGraph<'a> {
+    maze: &'a MyApp,
+    banned_nodes: &'a HashSet<(usize, usize)>,
+    banned_edges: &'a HashSet<Edge>,
+}
+
+impl Graph for MazeGraph<'_> {
+    type Node = (usize, usize);
+
+    fn neighbors(&self, cell: (usize, usize)) -> impl Iterator<Item = ((usize, usize), f64)> {
+        self.maze
+            .edges(cell)
+            .into_iter()
+            .filter(move |(next, _)| !self.banned_nodes.contains(next) && !self.banned_edges.contains(&(cell, *next)))
+    }
+}
+
+// Как обход по соседним клеткам учитывает односторонние коридоры
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Moves {
+    Forward,
+    // Против хода: так находятся клетки, из которых можно дойти до данной
+    Backward,
+    Undirected,
+}
+
+// Обычные шаги в соседние клетки прохода, каждый стоит один такт. В blocked не заходим.
+struct StepGraph<'a> {
+    maze: &'a MyApp,
+    blocked: &'a [(usize, usize)],
+    moves: Moves,
+}
+
+impl Graph for StepGraph<'_> {
+    type Node = (usize, usize);
+
+    fn neighbors(&self, cell: (usize, usize)) -> impl Iterator<Item = ((usize, usize), f64)> {
+        let maze = self.maze;
+        maze.adjacent(cell)
+            .into_iter()
+            .filter(move |&next| {
+                !self.blocked.contains(&next)
+                    && match self.moves {
+                        Moves::Forward => maze.allows_move(cell, next),
+                        Moves::Backward => maze.allows_move(next, cell),
+                        Moves::Undirected => true,
+                    }
+            })
+            .map(|next| (next, 1.0))
     }
 }
 
@@ -174,32 +626,346 @@ impl Default for MazeApp {
     }
 }
 
+impl MazeApp {
+    // Лабиринт с настройками прошлого сеанса; если сохранения нет — новый
+    pub fn restored() -> Self {
+        let mut inner = MyApp::default();
+        if !inner.load_settings() {
+            inner.seed = thread_rng().gen();
+            inner.regenerate();
+        }
+        MazeApp { inner }
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = self.inner.save_settings() {
+            println!("Не удалось сохранить настройки лабиринта: {}", err);
+        }
+    }
+}
+
 impl eframe::App for MazeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Пока фокус в текстовом поле, Ctrl+Z и Ctrl+Y остаются ему
+        let editing_text = ctx.memory(|m| m.focus().is_some());
+        let (undo_pressed, redo_pressed) = if editing_text {
+            (false, false)
+        } else {
+            ctx.input_mut(|i| {
+                let redo = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+                    || i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z);
+                (i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z), redo)
+            })
+        };
+        if undo_pressed {
+            self.inner.undo();
+        }
+        if redo_pressed {
+            self.inner.redo();
+        }
+
+        let tick = match self.inner.animation_start {
+            Some(start) => {
+                let tick = ((ctx.input(|i| i.time) - start) / timed::TICK_SECONDS) as usize;
+                let longest_agent = self.inner.agent_paths.iter().map(Vec::len).max().unwrap_or(0);
+                let path_ticks = self.inner.arrival_ticks(&self.inner.path).last().map_or(0, |&last| last + 1);
+                if tick < path_ticks.max(longest_agent)
+                    || !self.inner.gates.is_empty()
+                    || !self.inner.hazards.is_empty()
+                {
+                    ctx.request_repaint();
+                }
+                tick
+            }
+            None => 0,
+        };
+
         egui::SidePanel::left("left_panel")
             .resizable(false)
             .default_width(200.0)
             .show(ctx, |ui| {
-                ui.vertical(|ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(self.inner.history.can_undo(), egui::Button::new("Отменить")).clicked() {
+                            self.inner.undo();
+                        }
+                        if ui.add_enabled(self.inner.history.can_redo(), egui::Button::new("Повторить")).clicked() {
+                            self.inner.redo();
+                        }
+                    });
+
                     if ui.button("Сгенерировать лабиринт").clicked() {
+                        self.inner.record();
+                        self.inner.seed = thread_rng().gen();
                         self.inner.path.clear(); // Очищаем путь при генерации нового лабиринта
-                        self.inner.generate_maze();
+                        self.inner.regenerate();
                     }
 
                     if ui.button("Решить лабиринт").clicked() {
+                        self.inner.record();
                         self.inner.solve_maze();
+                        self.inner.animation_start = Some(ctx.input(|i| i.time));
                     }
 
+                    ui.horizontal(|ui| {
+                        ui.label("Зерно:");
+                        ui.add(egui::DragValue::new(&mut self.inner.seed));
+                        if ui.button("Построить").clicked() {
+                            self.inner.record();
+                            self.inner.regenerate();
+                        }
+                    });
+
                     ui.separator();
 
                     ui.label("Размер лабиринта:");
                     let mut new_size = self.inner.grid_size;
-                    if ui.add(egui::Slider::new(&mut new_size, 5..=251)).changed() {
-                        self.inner.grid_size = if new_size % 2 == 0 { new_size + 1 } else { new_size };
+                    let size_slider = ui.add(egui::Slider::new(&mut new_size, GRID_SIZES));
+                    let new_size = if new_size.is_multiple_of(2) { new_size + 1 } else { new_size };
+                    // Пересоздаём лабиринт, только когда ползунок отпущен. Размер меняем после
+                    // снимка, чтобы отмена вернула прежнюю сетку вместе с прежним размером.
+                    let size_committed = size_slider.drag_released() || (size_slider.changed() && !size_slider.dragged());
+                    if size_committed && new_size != self.inner.grid_size {
+                        self.inner.record();
+                        self.inner.grid_size = new_size;
                         self.inner.path.clear();
-                        self.inner.generate_maze();
+                        self.inner.regenerate();
+                    }
+
+                    ui.checkbox(&mut self.inner.edit_mode, "Редактировать стены щелчком");
+
+                    ui.label("Убрать тупиков, %:");
+                    ui.add(egui::Slider::new(&mut self.inner.braid_percent, 0..=100));
+
+                    ui.label("Мостов, %:");
+                    ui.add(egui::Slider::new(&mut self.inner.weave_percent, 0..=100));
+
+                    if ui.button("Все кратчайшие пути").clicked() {
+                        self.inner.record();
+                        self.inner.analyze_shortest_paths();
+                    }
+                    if self.inner.shortest_count > 0 {
+                        ui.label(format!("Кратчайших путей: {}", self.inner.shortest_count));
+                        if self.inner.shortest_count > self.inner.shortest_paths.len() as u128 {
+                            ui.label(format!("Для перебора доступны первые {}", shortest::ENUMERATE_LIMIT));
+                        }
+                        ui.horizontal(|ui| {
+                            let index = self.inner.shortest_index;
+                            let total = self.inner.shortest_paths.len();
+                            if ui.button("<").clicked() {
+                                self.inner.show_shortest_path(index + total - 1);
+                            }
+                            ui.label(format!("{} / {}", self.inner.shortest_index + 1, total));
+                            if ui.button(">").clicked() {
+                                self.inner.show_shortest_path(index + 1);
+                            }
+                        });
+                        ui.checkbox(&mut self.inner.show_heatmap, "Тепловая карта путей");
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("K лучших маршрутов:");
+                        ui.add(egui::DragValue::new(&mut self.inner.route_count).clamp_range(1..=yen::ROUTE_COLORS.len()));
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Показать").clicked() {
+                            self.inner.routes = self.inner.k_shortest_paths(self.inner.route_count);
+                            if self.inner.routes.is_empty() {
+                                self.inner.status = Status::Error("Путь не найден: выход недостижим".to_owned());
+                            }
+                        }
+                        if ui.add_enabled(!self.inner.routes.is_empty(), egui::Button::new("Скрыть")).clicked() {
+                            self.inner.routes.clear();
+                        }
+                    });
+                    for (index, (_, length)) in self.inner.routes.iter().enumerate() {
+                        ui.colored_label(yen::ROUTE_COLORS[index], format!("Маршрут {}: {:.1}", index + 1, length));
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Можно разбить стен:");
+                        ui.add(egui::DragValue::new(&mut self.inner.wall_budget).clamp_range(0..=20));
+                    });
+                    if ui.button("Решить с разбиванием стен").clicked() {
+                        self.inner.record();
+                        self.inner.solve_breaking_walls();
+                    }
+                    if !self.inner.break_lengths.is_empty() {
+                        ui.label(format!("Разбито стен: {}", self.inner.broken_walls.len()));
+                        for (limit, length) in self.inner.break_lengths.iter().enumerate() {
+                            let text = match length {
+                                Some(length) => format!("Бюджет {}: длина {}", limit, length),
+                                None => format!("Бюджет {}: пути нет", limit),
+                            };
+                            ui.label(text);
+                        }
+                    }
+
+                    ui.label("Дверей с ключами:");
+                    ui.add(egui::Slider::new(&mut self.inner.door_count, 0..=keys::KEY_COLORS.len()));
+                    if !self.inner.key_order.is_empty() {
+                        ui.label("Порядок ключей:");
+                        for (step, &key) in self.inner.key_order.iter().enumerate() {
+                            let (color, name) = keys::KEY_COLORS[key];
+                            ui.colored_label(color, format!("{}. {}", step + 1, name));
+                        }
+                    }
+
+                    ui.label("Края:");
+                    ui.horizontal(|ui| {
+                        let before = self.inner.topology;
+                        ui.radio_value(&mut self.inner.topology, topology::Topology::Plane, "Плоскость");
+                        ui.radio_value(&mut self.inner.topology, topology::Topology::Cylinder, "Цилиндр");
+                        ui.radio_value(&mut self.inner.topology, topology::Topology::Torus, "Тор");
+                        if self.inner.topology != before {
+                            let chosen = self.inner.topology;
+                            self.inner.topology = before;
+                            self.inner.record();
+                            self.inner.topology = chosen;
+                            self.inner.regenerate();
+                        }
+                    });
+
+                    ui.label("Движение:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.inner.connectivity, movement::Connectivity::Four, "4 стороны");
+                        ui.radio_value(&mut self.inner.connectivity, movement::Connectivity::Eight, "8 сторон");
+                    });
+                    ui.checkbox(&mut self.inner.open_grid, "Открытая карта");
+                    if self.inner.open_grid {
+                        ui.label("Препятствий, %:");
+                        ui.add(egui::Slider::new(&mut self.inner.obstacle_percent, 0..=60));
+                    }
+
+                    ui.label("Односторонних коридоров, %:");
+                    ui.add(egui::Slider::new(&mut self.inner.one_way_percent, 0..=100));
+
+                    ui.label("Пар порталов:");
+                    ui.add(egui::Slider::new(&mut self.inner.portal_count, 0..=portals::PORTAL_COLORS.len()));
+                    ui.label("Стоимость телепорта:");
+                    ui.add(egui::Slider::new(&mut self.inner.portal_cost, 1..=20));
+
+                    ui.label("Ворот по расписанию:");
+                    ui.add(egui::Slider::new(&mut self.inner.gate_count, 0..=10));
+                    ui.label("Патрулей:");
+                    ui.add(egui::Slider::new(&mut self.inner.hazard_count, 0..=10));
+                    if self.inner.animation_start.is_some() {
+                        ui.label(format!("Такт: {}", tick));
+                    }
+
+                    ui.separator();
+
+                    ui.label("Агентов:");
+                    ui.add(egui::Slider::new(&mut self.inner.agent_count, 1..=agents::AGENT_COLORS.len()));
+                    if ui.button("Расставить агентов").clicked() {
+                        self.inner.record();
+                        self.inner.place_agents();
+                    }
+                    if ui.add_enabled(!self.inner.agents.is_empty(), egui::Button::new("Спланировать маршруты")).clicked() {
+                        self.inner.record();
+                        match self.inner.plan_agents() {
+                            Ok(()) => {
+                                self.inner.status = Status::Empty;
+                                self.inner.animation_start = Some(ctx.input(|i| i.time));
+                            }
+                            Err(index) => {
+                                self.inner.status = Status::Error(format!("Не удалось спланировать маршрут агента {}", index + 1));
+                            }
+                        }
+                    }
+
+                    ui.separator();
+
+                    ui.label("Маска (чёрно-белое изображение):");
+                    ui.text_edit_singleline(&mut self.inner.mask_path);
+                    ui.horizontal(|ui| {
+                        if ui.button("Загрузить маску").clicked() {
+                            self.inner.record();
+                            self.inner.load_mask();
+                        }
+                        if ui.add_enabled(self.inner.mask_image.is_some(), egui::Button::new("Убрать")).clicked() {
+                            self.inner.record();
+                            self.inner.mask_image = None;
+                            self.inner.regenerate();
+                        }
+                    });
+
+                    ui.separator();
+
+                    ui.label("Лабиринт с картинки:");
+                    ui.text_edit_singleline(&mut self.inner.image_path);
+                    if ui.button("Открыть изображение").clicked() {
+                        self.inner.record();
+                        self.inner.open_image();
+                    }
+                    if self.inner.source_image.is_some() {
+                        ui.label("Порог яркости стен:");
+                        let threshold_changed = ui.add(egui::Slider::new(&mut self.inner.image_threshold, 1..=255)).changed();
+                        ui.label("Клеток по ширине:");
+                        let resolution_changed = ui.add(egui::Slider::new(&mut self.inner.image_resolution, 10..=400)).changed();
+                        if threshold_changed || resolution_changed {
+                            self.inner.threshold_image();
+                        }
+                        if ui.add_enabled(!self.inner.path.is_empty(), egui::Button::new("Сохранить решение")).clicked() {
+                            self.inner.export_solution();
+                        }
+                    }
+
+                    ui.separator();
+
+                    ui.label("Листы для печати:");
+                    ui.horizontal(|ui| {
+                        ui.label("Лабиринтов:");
+                        ui.add(egui::DragValue::new(&mut self.inner.print_count).clamp_range(1..=60));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.inner.print_format, print::PageFormat::A4, "A4");
+                        ui.radio_value(&mut self.inner.print_format, print::PageFormat::Letter, "Letter");
+                    });
+                    ui.label("Папка:");
+                    ui.text_edit_singleline(&mut self.inner.print_folder);
+                    if ui.button("Экспорт листов (SVG)").clicked() {
+                        let folder = std::path::PathBuf::from(self.inner.print_folder.trim());
+                        self.inner.status = match self.inner.export_puzzle_sheets(self.inner.print_count, self.inner.print_format, &folder) {
+                            Ok(files) => Status::Info(format!("Сохранено файлов: {} в {}", files.len(), folder.display())),
+                            Err(err) => Status::Error(format!("Не удалось сохранить листы: {}", err)),
+                        };
+                    }
+
+                    ui.separator();
+                    ui.label("Маршрут словами:");
+                    self.inner.hovered_direction = None;
+                    egui::ScrollArea::vertical().id_source("directions").max_height(200.0).show(ui, |ui| {
+                        for (index, instruction) in self.inner.path_directions().iter().enumerate() {
+                            let label = ui.add(egui::Label::new(format!("{}. {}", index + 1, instruction.text)).sense(egui::Sense::hover()));
+                            if label.hovered() {
+                                self.inner.hovered_direction = Some((instruction.start, instruction.end));
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.inner.directions_file);
+                        if ui.button("Сохранить").clicked() {
+                            let file = std::path::PathBuf::from(self.inner.directions_file.trim());
+                            self.inner.status = match self.inner.export_directions(&file) {
+                                Ok(()) => Status::Info(format!("Сохранено: {}", file.display())),
+                                Err(err) => Status::Error(format!("Не удалось сохранить: {}", err)),
+                            };
+                        }
+                    });
+
+                    match &self.inner.status {
+                        Status::Empty => {}
+                        Status::Info(text) => {
+                            ui.label(text);
+                        }
+                        Status::Error(text) => {
+                            ui.colored_label(egui::Color32::RED, text);
+                        }
                     }
 
+                    ui.separator();
+
                     ui.label("Цвет пути:");
                     ui.color_edit_button_srgba(&mut self.inner.path_color);
 
@@ -211,15 +977,79 @@ impl eframe::App for MazeApp {
                 });
             });
 
+        if let (Some(image), None) = (&self.inner.source_image, &self.inner.source_texture) {
+            let size = [image.width() as usize, image.height() as usize];
+            self.inner.source_texture = Some(ctx.load_texture(
+                "maze_source",
+                egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+                egui::TextureOptions::default(),
+            ));
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let available_size = ui.available_size();
-            let cell_size = (available_size.x.min(available_size.y) / self.inner.grid_size as f32).max(1.0);
-            let total_maze_size = cell_size * self.inner.grid_size as f32;
-            let offset_x = (available_size.x - total_maze_size).max(0.0) / 2.0;
-            let offset_y = (available_size.y - total_maze_size).max(0.0) / 2.0;
+            let (response, painter) = ui.allocate_painter(available_size, egui::Sense::click());
+            let rows = self.inner.grid.len();
+            let cols = self.inner.grid[0].len();
+            let cell_size = (available_size.x / cols as f32).min(available_size.y / rows as f32).max(1.0);
+            let maze_size = egui::vec2(cell_size * cols as f32, cell_size * rows as f32);
+            let origin = response.rect.min + ((available_size - maze_size) / 2.0).max(egui::Vec2::ZERO);
+            let cell_rect = |row: usize, col: usize| {
+                egui::Rect::from_min_size(
+                    origin + egui::vec2(col as f32 * cell_size, row as f32 * cell_size),
+                    egui::vec2(cell_size, cell_size),
+                )
+            };
+            let clicked_cell = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+                .map(|pos| (pos - origin) / cell_size)
+                .filter(|local| local.x >= 0.0 && local.y >= 0.0 && (local.y as usize) < rows && (local.x as usize) < cols)
+                .map(|local| (local.y as usize, local.x as usize));
+
+            if let Some(texture) = &self.inner.source_texture {
+                painter.image(
+                    texture.id(),
+                    egui::Rect::from_min_size(origin, maze_size),
+                    egui::Rect::from_min_max([0.0, 0.0].into(), [1.0, 1.0].into()),
+                    egui::Color32::WHITE,
+                );
+
+                let overlay = egui::Color32::from_rgba_unmultiplied(
+                    self.inner.path_color.r(),
+                    self.inner.path_color.g(),
+                    self.inner.path_color.b(),
+                    160,
+                );
+                for &(row, col) in &self.inner.path {
+                    painter.rect_filled(cell_rect(row, col), 0.0, overlay);
+                }
+                painter.circle_filled(cell_rect(self.inner.entrance.0, self.inner.entrance.1).center(), cell_size.max(4.0), egui::Color32::BLUE);
+                if !self.inner.picking_exit && !self.inner.path.is_empty() {
+                    painter.circle_filled(cell_rect(self.inner.exit.0, self.inner.exit.1).center(), cell_size.max(4.0), egui::Color32::RED);
+                }
+
+                if let Some(cell) = clicked_cell {
+                    if self.inner.picking_exit {
+                        self.inner.record();
+                    }
+                    self.inner.pick_endpoint(cell);
+                }
+                return;
+            }
+
+            if let Some(cell) = clicked_cell.filter(|_| self.inner.edit_mode) {
+                self.inner.toggle_wall(cell);
+            }
+
+            for row in 0..rows {
+                for col in 0..cols {
+                    if let Some(mask) = &self.inner.mask {
+                        if self.inner.grid[row][col] && !self.inner.is_visible_wall(mask, row, col) {
+                            continue;
+                        }
+                    }
 
-            for row in 0..self.inner.grid_size {
-                for col in 0..self.inner.grid_size {
                     let color = if self.inner.grid[row][col] {
                         self.inner.wall_color // Стена
                     } else if self.inner.path.contains(&(row, col)) {
@@ -228,16 +1058,165 @@ impl eframe::App for MazeApp {
                         self.inner.passage_color // Проход
                     };
 
-                    ui.painter().rect_filled(
-                        egui::Rect::from_min_size(
-                            egui::Pos2::new(offset_x + col as f32 * cell_size, offset_y + row as f32 * cell_size),
-                            egui::vec2(cell_size, cell_size),
-                        ),
-                        0.0,
-                        color,
+                    let rect = cell_rect(row, col);
+                    painter.rect_filled(rect, 0.0, color);
+                    if let Some(&door) = self.inner.doors.get(&(row, col)) {
+                        painter.rect_filled(rect.shrink(cell_size * 0.1), 0.0, keys::KEY_COLORS[door].0);
+                        painter.rect_stroke(rect.shrink(cell_size * 0.1), 0.0, egui::Stroke::new(1.0, egui::Color32::BLACK));
+                    }
+                    if let Some(&key) = self.inner.keys.get(&(row, col)) {
+                        painter.circle_filled(rect.center(), cell_size * 0.35, keys::KEY_COLORS[key].0);
+                    }
+                    if let Some(&(d_row, d_col)) = self.inner.one_way.get(&(row, col)) {
+                        let direction = egui::vec2(d_col as f32, d_row as f32) * cell_size * 0.7;
+                        painter.arrow(
+                            rect.center() - direction / 2.0,
+                            direction,
+                            egui::Stroke::new((cell_size * 0.1).max(1.0), egui::Color32::from_rgb(255, 140, 0)),
+                        );
+                    }
+                    if let Some(portal) = self.inner.portal_color((row, col)) {
+                        painter.circle_stroke(rect.center(), cell_size * 0.35, egui::Stroke::new((cell_size * 0.15).max(1.0), portal));
+                    }
+                }
+            }
+
+            // Мост: по бокам перекрёстка перила вдоль моста, а туннель под ним темнее прохода
+            let rail = egui::Stroke::new((cell_size * 0.15).max(1.0), self.inner.wall_color);
+            for (&(row, col), &(t_row, t_col)) in &self.inner.crossings {
+                for (d_row, d_col) in [(t_row, t_col), (-t_row, -t_col)] {
+                    if let Some((u_row, u_col)) = self.inner.step((row, col), (d_row, d_col)) {
+                        if !self.inner.path.contains(&(u_row, u_col)) {
+                            painter.rect_filled(cell_rect(u_row, u_col), 0.0, self.inner.passage_color.linear_multiply(0.6));
+                        }
+                    }
+                }
+                let rect = cell_rect(row, col).shrink(cell_size * 0.1);
+                if t_row == 0 {
+                    painter.line_segment([rect.left_top(), rect.left_bottom()], rail);
+                    painter.line_segment([rect.right_top(), rect.right_bottom()], rail);
+                } else {
+                    painter.line_segment([rect.left_top(), rect.right_top()], rail);
+                    painter.line_segment([rect.left_bottom(), rect.right_bottom()], rail);
+                }
+            }
+
+            // Швы склеенных краёв: пунктир по краю и стрелки там, где проход уходит за шов
+            let seam = egui::Stroke::new((cell_size * 0.15).max(1.5), egui::Color32::from_rgb(186, 85, 211));
+            let maze_rect = egui::Rect::from_min_size(origin, maze_size);
+            let dash = (cell_size * 0.5).max(3.0);
+            if self.inner.topology.wraps_cols() {
+                for x in [maze_rect.left(), maze_rect.right()] {
+                    painter.extend(egui::Shape::dashed_line(&[egui::pos2(x, maze_rect.top()), egui::pos2(x, maze_rect.bottom())], seam, dash, dash));
+                }
+                for row in (0..rows).filter(|&row| !self.inner.grid[row][0] && !self.inner.grid[row][cols - 1]) {
+                    let arrow = egui::vec2(cell_size * 0.6, 0.0);
+                    painter.arrow(cell_rect(row, 0).center(), -arrow, seam);
+                    painter.arrow(cell_rect(row, cols - 1).center(), arrow, seam);
+                }
+            }
+            if self.inner.topology.wraps_rows() {
+                for y in [maze_rect.top(), maze_rect.bottom()] {
+                    painter.extend(egui::Shape::dashed_line(&[egui::pos2(maze_rect.left(), y), egui::pos2(maze_rect.right(), y)], seam, dash, dash));
+                }
+                for col in (0..cols).filter(|&col| !self.inner.grid[0][col] && !self.inner.grid[rows - 1][col]) {
+                    let arrow = egui::vec2(0.0, cell_size * 0.6);
+                    painter.arrow(cell_rect(0, col).center(), -arrow, seam);
+                    painter.arrow(cell_rect(rows - 1, col).center(), arrow, seam);
+                }
+            }
+
+            let cross = egui::Stroke::new((cell_size * 0.12).max(1.0), egui::Color32::RED);
+            for &(row, col) in &self.inner.broken_walls {
+                let rect = cell_rect(row, col).shrink(cell_size * 0.15);
+                painter.rect_filled(rect, 0.0, self.inner.path_color);
+                painter.line_segment([rect.left_top(), rect.right_bottom()], cross);
+                painter.line_segment([rect.right_top(), rect.left_bottom()], cross);
+            }
+
+            if self.inner.show_heatmap {
+                let busiest = self.inner.path_usage.values().copied().max().unwrap_or(1).max(1);
+                for (&(row, col), &usage) in &self.inner.path_usage {
+                    let share = (usage as f64 / busiest as f64) as f32;
+                    let color = egui::Color32::from_rgba_unmultiplied(255, 69, 0, (40.0 + 215.0 * share) as u8);
+                    painter.rect_filled(cell_rect(row, col), 0.0, color);
+                }
+            }
+
+            // Маршруты рисуем от худшего к лучшему, лучший — сверху и тоньше остальных
+            for (index, (route, _)) in self.inner.routes.iter().enumerate().rev() {
+                let points: Vec<egui::Pos2> = route.iter().map(|&(row, col)| cell_rect(row, col).center()).collect();
+                let width = cell_size * (0.2 + 0.08 * index as f32);
+                painter.add(egui::Shape::line(points, egui::Stroke::new(width.max(1.0), yen::ROUTE_COLORS[index])));
+            }
+
+            if let Some((start, end)) = self.inner.hovered_direction {
+                if let Some(segment) = self.inner.path.get(start..=end) {
+                    for &(row, col) in segment {
+                        painter.rect_filled(cell_rect(row, col), 0.0, egui::Color32::from_rgba_unmultiplied(255, 215, 0, 180));
+                    }
+                }
+            }
+
+            let gate_stroke = egui::Stroke::new((cell_size * 0.15).max(1.0), egui::Color32::DARK_GRAY);
+            for gate in &self.inner.gates {
+                let rect = cell_rect(gate.cell.0, gate.cell.1).shrink(cell_size * 0.1);
+                if gate.is_open(tick) {
+                    painter.rect_stroke(rect, 0.0, gate_stroke);
+                } else {
+                    painter.rect_filled(rect, 0.0, egui::Color32::GRAY);
+                    painter.rect_stroke(rect, 0.0, gate_stroke);
+                }
+            }
+            for hazard in &self.inner.hazards {
+                let (row, col) = hazard.position(tick);
+                painter.circle_filled(cell_rect(row, col).center(), cell_size * 0.4, egui::Color32::RED);
+            }
+
+            for (index, agent) in self.inner.agents.iter().enumerate() {
+                let color = agents::AGENT_COLORS[index % agents::AGENT_COLORS.len()];
+                let (row, col) = match self.inner.agent_paths.get(index) {
+                    Some(path) => path[tick.min(path.len() - 1)],
+                    None => agent.start,
+                };
+                painter.rect_stroke(
+                    cell_rect(agent.goal.0, agent.goal.1).shrink(cell_size * 0.1),
+                    0.0,
+                    egui::Stroke::new((cell_size * 0.15).max(1.0), color),
+                );
+                painter.circle_filled(cell_rect(row, col).center(), cell_size * 0.4, color);
+            }
+
+            if self.inner.animation_start.is_some() && !self.inner.path.is_empty() {
+                // Долгий переход (диагональ, туннель, портал) идёт несколько тактов: до прихода стоим на месте
+                let step = self.inner.arrival_ticks(&self.inner.path).partition_point(|&arrival| arrival <= tick) - 1;
+                let (row, col) = self.inner.path[step];
+                painter.circle_filled(cell_rect(row, col).center(), cell_size * 0.4, egui::Color32::from_rgb(30, 144, 255));
+
+                // Прыжок через портал: на этом такте рисуем луч от входа к выходу портала
+                if step > 0 && self.inner.portal_partner(self.inner.path[step - 1]) == Some((row, col)) {
+                    let (from_row, from_col) = self.inner.path[step - 1];
+                    let color = self.inner.portal_color((row, col)).unwrap_or(egui::Color32::WHITE);
+                    painter.line_segment(
+                        [cell_rect(from_row, from_col).center(), cell_rect(row, col).center()],
+                        egui::Stroke::new((cell_size * 0.2).max(2.0), color),
                     );
                 }
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_mask_keeps_its_proportions() {
+        let maze = MyApp { mask_image: Some(image::GrayImage::new(40, 10)), ..MyApp::default() };
+        let mask = maze.mask_grid().unwrap();
+        let inside_rows: Vec<usize> = (0..mask.len()).filter(|&row| mask[row].iter().any(|&inside| inside)).collect();
+        assert_eq!(inside_rows, (8..13).collect::<Vec<_>>());
+        assert!(mask[10].iter().all(|&inside| inside));
+    }
+}
@@ -0,0 +1,181 @@
+use eframe::egui;
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet};
+
+use super::MyApp;
+use crate::pathfinding::{self, Graph};
+
+pub const AGENT_COLORS: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(30, 144, 255),
+    egui::Color32::from_rgb(255, 99, 71),
+    egui::Color32::from_rgb(50, 205, 50),
+    egui::Color32::from_rgb(255, 215, 0),
+    egui::Color32::from_rgb(238, 130, 238),
+    egui::Color32::from_rgb(0, 206, 209),
+    egui::Color32::from_rgb(255, 165, 0),
+    egui::Color32::from_rgb(210, 180, 140),
+];
+
+#[derive(Debug, Clone)]
+pub struct Agent {
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+}
+
+// Переход из клетки в клетку на такте
+type Move = ((usize, usize), (usize, usize), usize);
+
+// Таблица резервирований уже спланированных агентов
+#[derive(Default)]
+struct Reservations {
+    cells: HashSet<((usize, usize), usize)>,
+    moves: HashSet<Move>,
+    // Агент, дошедший до цели, стоит на ней до конца
+    parked: HashMap<(usize, usize), usize>,
+}
+
+impl Reservations {
+    fn is_free(&self, from: (usize, usize), to: (usize, usize), tick: usize) -> bool {
+        !self.cells.contains(&(to, tick + 1))
+            && !self.moves.contains(&(to, from, tick))
+            && self.parked.get(&to).is_none_or(|&since| tick + 1 < since)
+    }
+
+    fn reserve(&mut self, path: &[(usize, usize)]) {
+        for (tick, &cell) in path.iter().enumerate() {
+            self.cells.insert((cell, tick));
+            if tick + 1 < path.len() {
+                self.moves.insert((cell, path[tick + 1], tick));
+            }
+        }
+        let last = path.len() - 1;
+        self.parked.insert(path[last], last);
+    }
+
+    // Последний такт, на котором клетку занимает кто-то другой
+    fn last_use(&self, cell: (usize, usize)) -> Option<usize> {
+        self.cells.iter().filter(|(reserved, _)| *reserved == cell).map(|&(_, tick)| tick).max()
+    }
+}
+
+impl MyApp {
+    pub(super) fn place_agents(&mut self) {
+        let mut free: Vec<(usize, usize)> = (0..self.grid.len())
+            .flat_map(|row| (0..self.grid[0].len()).map(move |col| (row, col)))
+            .filter(|&(row, col)| !self.grid[row][col])
+            .collect();
+        free.shuffle(&mut rand::thread_rng());
+
+        let count = self.agent_count.min(free.len() / 2);
+        self.agents = (0..count).map(|i| Agent { start: free[2 * i], goal: free[2 * i + 1] }).collect();
+        self.agent_paths.clear();
+    }
+
+    // Приоритетное планирование: агенты по очереди ищут путь A* в пространстве-времени,
+    // обходя клетки и переходы, занятые предыдущими. Если агенту не нашлось пути,
+    // поднимаем его в начало очереди и пробуем снова.
+    pub(super) fn plan_agents(&mut self) -> Result<(), usize> {
+        // Если цель недостижима даже в пустом лабиринте, поиск во времени не нужен
+        let mut distances = Vec::with_capacity(self.agents.len());
+        for (index, agent) in self.agents.iter().enumerate() {
+            match self.step_distance(agent.start, agent.goal) {
+                Some(distance) => distances.push(distance),
+                None => return Err(index),
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.agents.len()).collect();
+        let mut failed = 0;
+        for _ in 0..self.agents.len() * 2 {
+            let mut reservations = Reservations::default();
+            let mut paths = vec![Vec::new(); self.agents.len()];
+            let mut blocked = None;
+            for &index in &order {
+                match self.space_time_astar(&self.agents[index], distances[index], &reservations) {
+                    Some(path) => {
+                        reservations.reserve(&path);
+                        paths[index] = path;
+                    }
+                    None => {
+                        blocked = Some(index);
+                        break;
+                    }
+                }
+            }
+
+            match blocked {
+                None => {
+                    self.agent_paths = paths;
+                    return Ok(());
+                }
+                Some(index) => {
+                    failed = index;
+                    order.retain(|&other| other != index);
+                    order.insert(0, index);
+                }
+            }
+        }
+        Err(failed)
+    }
+
+    // distance — длина пути без других агентов. Ожидания и обходы не удлиняют путь больше чем
+    // на число чужих резервирований, поэтому горизонт — удвоенная сумма, а не весь лабиринт.
+    fn space_time_astar(
+        &self,
+        agent: &Agent,
+        distance: usize,
+        reservations: &Reservations,
+    ) -> Option<Vec<(usize, usize)>> {
+        let graph = SpaceTime { maze: self, reservations, horizon: 2 * (distance + reservations.cells.len()) };
+        let settle_after = reservations.last_use(agent.goal).map_or(0, |tick| tick + 1);
+        let (states, _) = pathfinding::astar(
+            &graph,
+            (agent.start, 0),
+            |(cell, tick)| cell == agent.goal && tick >= settle_after,
+            |(cell, _)| self.grid_distance(cell, agent.goal) as f64,
+        )?;
+        Some(states.into_iter().map(|(cell, _)| cell).collect())
+    }
+}
+
+// Граф (клетка, такт) с учётом чужих резервирований; дальше horizon не ищем
+struct SpaceTime<'a> {
+    maze: &'a MyApp,
+    reservations: &'a Reservations,
+    horizon: usize,
+}
+
+impl Graph for SpaceTime<'_> {
+    type Node = ((usize, usize), usize);
+
+    fn neighbors(&self, (cell, tick): ((usize, usize), usize)) -> impl Iterator<Item = (((usize, usize), usize), f64)> {
+        let mut moves = self.maze.neighbors(cell);
+        moves.push(cell); // ожидание на месте
+        moves
+            .into_iter()
+            .filter(move |&next| tick < self.horizon && self.reservations.is_free(cell, next, tick))
+            .map(move |next| ((next, tick + 1), 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreachable_goal_fails_without_searching_in_time() {
+        let mut maze = MyApp::default();
+        for (row, col) in [(9, 10), (11, 10), (10, 9), (10, 11)] {
+            maze.grid[row][col] = true;
+        }
+        maze.agents = vec![
+            Agent { start: (1, 1), goal: (19, 19) },
+            Agent { start: (1, 19), goal: (10, 10) },
+        ];
+        assert_eq!(maze.plan_agents(), Err(1));
+
+        maze.agents.pop();
+        assert_eq!(maze.plan_agents(), Ok(()));
+        assert_eq!(maze.agent_paths[0].last(), Some(&(19, 19)));
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::MyApp;
+
+// Что описывает инструкция: ходьбу по прямой, ожидание на месте или особый шаг
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Walk,
+    Wait,
+    Special,
+}
+
+// Одна инструкция маршрута и отрезок пути (индексы в path), который она описывает
+pub(super) struct Instruction {
+    pub(super) text: String,
+    pub(super) start: usize,
+    pub(super) end: usize,
+    kind: Kind,
+}
+
+fn direction_name(delta: (isize, isize)) -> &'static str {
+    match delta {
+        (-1, 0) => "вверх",
+        (1, 0) => "вниз",
+        (0, -1) => "влево",
+        (0, 1) => "вправо",
+        (-1, -1) => "вверх-влево",
+        (-1, 1) => "вверх-вправо",
+        (1, -1) => "вниз-влево",
+        (1, 1) => "вниз-вправо",
+        _ => "?",
+    }
+}
+
+// 1 такт, 2 такта, 5 тактов, 21 такт
+fn ticks(count: usize) -> String {
+    let ending = match (count % 10, count % 100) {
+        (_, 11..=14) => "ов",
+        (1, _) => "",
+        (2..=4, _) => "а",
+        _ => "ов",
+    };
+    format!("{} такт{}", count, ending)
+}
+
+impl MyApp {
+    // Сжимаем путь в инструкции: подряд идущие шаги в одну сторону и ожидания на месте
+    // объединяются, туннель под мостом и прыжок через портал — отдельные инструкции
+    pub(super) fn path_directions(&self) -> Vec<Instruction> {
+        let mut instructions: Vec<Instruction> = Vec::new();
+        // Направление последнего шага: по нему видно, поворот это или продолжение пути
+        let mut heading: Option<(isize, isize)> = None;
+
+        for (index, step) in self.path.windows(2).enumerate() {
+            let single = |text: String| Instruction { text, start: index, end: index + 1, kind: Kind::Special };
+            if step[0] == step[1] {
+                match instructions.last_mut() {
+                    Some(last) if last.kind == Kind::Wait => last.end = index + 1,
+                    _ => instructions.push(Instruction { kind: Kind::Wait, ..single(String::new()) }),
+                }
+                continue;
+            }
+            let delta = self.step_delta(step[0], step[1]);
+            if self.is_under_passage(step[0], step[1]) {
+                instructions.push(single("Пройдите под мостом".to_owned()));
+                heading = Some((delta.0.signum(), delta.1.signum()));
+                continue;
+            }
+            if self.portal_partner(step[0]) == Some(step[1]) {
+                instructions.push(single(format!("Телепорт в ({}, {})", step[1].0, step[1].1)));
+                heading = None;
+                continue;
+            }
+
+            match instructions.last_mut() {
+                Some(last) if last.kind == Kind::Walk && heading == Some(delta) => last.end = index + 1,
+                _ => {
+                    let verb = match heading {
+                        Some(previous) if previous != delta => "Поверните",
+                        _ => "Идите",
+                    };
+                    instructions.push(Instruction { kind: Kind::Walk, ..single(format!("{} {}", verb, direction_name(delta))) });
+                }
+            }
+            heading = Some(delta);
+        }
+
+        for instruction in &mut instructions {
+            let count = instruction.end - instruction.start;
+            match instruction.kind {
+                Kind::Walk => instruction.text = format!("{} и пройдите {}", instruction.text, count),
+                Kind::Wait => instruction.text = format!("Подождите {}", ticks(count)),
+                Kind::Special => {}
+            }
+        }
+        if !self.path.is_empty() {
+            let last = self.path.len() - 1;
+            instructions.push(Instruction { text: "Выход".to_owned(), start: last, end: last, kind: Kind::Special });
+        }
+        instructions
+    }
+
+    pub(super) fn export_directions(&self, file: &Path) -> io::Result<()> {
+        let text: String = self
+            .path_directions()
+            .iter()
+            .enumerate()
+            .map(|(index, instruction)| format!("{}. {}\n", index + 1, instruction.text))
+            .collect();
+        fs::write(file, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(maze: &MyApp) -> Vec<String> {
+        maze.path_directions().into_iter().map(|instruction| instruction.text).collect()
+    }
+
+    #[test]
+    fn waits_and_portals_are_separate_steps() {
+        let maze = MyApp {
+            portals: vec![((1, 3), (2, 3))],
+            path: vec![(1, 1), (1, 2), (1, 2), (1, 2), (1, 3), (2, 3), (3, 3), (3, 4)],
+            ..MyApp::default()
+        };
+        assert_eq!(
+            texts(&maze),
+            [
+                "Идите вправо и пройдите 1",
+                "Подождите 2 такта",
+                "Идите вправо и пройдите 1",
+                "Телепорт в (2, 3)",
+                "Идите вниз и пройдите 1",
+                "Поверните вправо и пройдите 1",
+                "Выход",
+            ]
+        );
+    }
+
+    #[test]
+    fn tick_endings() {
+        assert_eq!([ticks(1), ticks(3), ticks(5), ticks(12), ticks(21)], ["1 такт", "3 такта", "5 тактов", "12 тактов", "21 такт"]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{agents, timed, topology, MyApp, Status};
+
+const HISTORY_LIMIT: usize = 50;
+
+// Всё, что меняют генерация, смена размера, правка стен и решение
+pub struct Snapshot {
+    grid: Vec<Vec<bool>>,
+    grid_size: usize,
+    topology: topology::Topology,
+    seed: u64,
+    entrance: (usize, usize),
+    exit: (usize, usize),
+    path: Vec<(usize, usize)>,
+    mask: Option<Vec<Vec<bool>>>,
+    source_image: Option<image::RgbaImage>,
+    doors: HashMap<(usize, usize), usize>,
+    keys: HashMap<(usize, usize), usize>,
+    key_order: Vec<usize>,
+    gates: Vec<timed::Gate>,
+    hazards: Vec<timed::Hazard>,
+    portals: Vec<((usize, usize), (usize, usize))>,
+    one_way: HashMap<(usize, usize), (isize, isize)>,
+    crossings: HashMap<(usize, usize), (isize, isize)>,
+    agents: Vec<agents::Agent>,
+    agent_paths: Vec<Vec<(usize, usize)>>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Самые старые снимки вытесняются, когда их больше HISTORY_LIMIT
+    fn push_undo(&mut self, snapshot: Snapshot) {
+        self.undo.push_back(snapshot);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl MyApp {
+    pub(super) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.grid.clone(),
+            grid_size: self.grid_size,
+            topology: self.topology,
+            seed: self.seed,
+            entrance: self.entrance,
+            exit: self.exit,
+            path: self.path.clone(),
+            mask: self.mask.clone(),
+            source_image: self.source_image.clone(),
+            doors: self.doors.clone(),
+            keys: self.keys.clone(),
+            key_order: self.key_order.clone(),
+            gates: self.gates.clone(),
+            hazards: self.hazards.clone(),
+            portals: self.portals.clone(),
+            one_way: self.one_way.clone(),
+            crossings: self.crossings.clone(),
+            agents: self.agents.clone(),
+            agent_paths: self.agent_paths.clone(),
+        }
+    }
+
+    pub(super) fn restore(&mut self, snapshot: Snapshot) {
+        self.grid = snapshot.grid;
+        self.grid_size = snapshot.grid_size;
+        self.topology = snapshot.topology;
+        self.seed = snapshot.seed;
+        self.entrance = snapshot.entrance;
+        self.exit = snapshot.exit;
+        self.path = snapshot.path;
+        self.mask = snapshot.mask;
+        self.source_image = snapshot.source_image;
+        self.source_texture = None;
+        self.doors = snapshot.doors;
+        self.keys = snapshot.keys;
+        self.key_order = snapshot.key_order;
+        self.gates = snapshot.gates;
+        self.hazards = snapshot.hazards;
+        self.portals = snapshot.portals;
+        self.one_way = snapshot.one_way;
+        self.crossings = snapshot.crossings;
+        self.agents = snapshot.agents;
+        self.agent_paths = snapshot.agent_paths;
+        self.clear_overlays();
+        self.picking_exit = false;
+        self.animation_start = None;
+        self.status = Status::Empty;
+    }
+
+    // Запоминает текущее состояние перед изменением
+    pub(super) fn record(&mut self) {
+        let snapshot = self.snapshot();
+        self.history.push_undo(snapshot);
+        self.history.redo.clear();
+    }
+
+    pub(super) fn undo(&mut self) {
+        if let Some(snapshot) = self.history.undo.pop_back() {
+            let current = self.snapshot();
+            self.history.redo.push(current);
+            self.restore(snapshot);
+        }
+    }
+
+    pub(super) fn redo(&mut self) {
+        if let Some(snapshot) = self.history.redo.pop() {
+            let current = self.snapshot();
+            self.history.push_undo(current);
+            self.restore(snapshot);
+        }
+    }
+
+    pub(super) fn toggle_wall(&mut self, (row, col): (usize, usize)) {
+        self.record();
+        self.grid[row][col] = !self.grid[row][col];
+        // Стена на месте моста, двери, ключа, портала или одностороннего прохода убирает и их
+        let cell = (row, col);
+        self.crossings.remove(&cell);
+        self.doors.remove(&cell);
+        self.keys.remove(&cell);
+        self.one_way.remove(&cell);
+        self.portals.retain(|&(from, to)| from != cell && to != cell);
+        self.path.clear();
+        self.key_order.clear();
+        self.agent_paths.clear();
+        self.clear_overlays();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_restores_size_together_with_grid() {
+        let mut maze = MyApp { grid_size: 11, ..MyApp::default() };
+        maze.generate_maze().unwrap();
+        maze.record();
+        maze.grid_size = 15;
+        maze.generate_maze().unwrap();
+
+        maze.undo();
+        assert_eq!((maze.grid_size, maze.grid.len()), (11, 11));
+        maze.redo();
+        assert_eq!((maze.grid_size, maze.grid.len()), (15, 15));
+    }
+    #[test]
+    fn toggling_a_wall_clears_overlays_and_cell_features() {
+        let mut maze = MyApp::default();
+        maze.generate_maze().unwrap();
+        maze.solve_maze();
+        let cell = maze.path[maze.path.len() / 2];
+        maze.doors.insert(cell, 0);
+        maze.keys.insert(cell, 1);
+        maze.one_way.insert(cell, (0, 1));
+        maze.broken_walls.push((0, 0));
+        maze.routes.push((maze.path.clone(), 1.0));
+        maze.path_usage.insert(cell, 1);
+
+        maze.toggle_wall(cell);
+        assert!(maze.path.is_empty() && maze.routes.is_empty() && maze.broken_walls.is_empty() && maze.path_usage.is_empty());
+        assert!(!maze.doors.contains_key(&cell) && !maze.keys.contains_key(&cell) && !maze.one_way.contains_key(&cell));
+    }
+}
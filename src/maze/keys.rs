@@ -0,0 +1,225 @@
+use eframe::egui;
+use rand::{Rng, seq::SliceRandom};
+use std::collections::{HashMap, HashSet};
+
+use super::{Moves, MyApp, StepGraph};
+use crate::pathfinding::{self, Graph};
+
+pub const KEY_COLORS: [(egui::Color32, &str); 4] = [
+    (egui::Color32::from_rgb(220, 20, 60), "красный"),
+    (egui::Color32::from_rgb(30, 144, 255), "синий"),
+    (egui::Color32::from_rgb(255, 215, 0), "жёлтый"),
+    (egui::Color32::from_rgb(186, 85, 211), "фиолетовый"),
+];
+
+// Сколько клеток пути без обхода пробуем под каждую дверь, прежде чем сдаться
+const DOOR_ATTEMPTS: usize = 8;
+
+// Состояние поиска: клетка и набор подобранных ключей (бит на цвет)
+pub(super) type KeyState = ((usize, usize), u32);
+
+// Путь и порядок подобранных по нему ключей
+pub(super) type KeyedPath = (Vec<(usize, usize)>, Vec<usize>);
+
+// Граф состояний (клетка, ключи): в дверь можно войти, только имея ключ её цвета
+struct KeyGraph<'a>(&'a MyApp);
+
+impl Graph for KeyGraph<'_> {
+    type Node = KeyState;
+
+    fn neighbors(&self, (cell, keys): KeyState) -> impl Iterator<Item = (KeyState, f64)> {
+        let maze = self.0;
+        maze.edges(cell)
+            .into_iter()
+            .filter(move |&(next, _)| maze.can_enter(next, keys))
+            .map(move |(next, step)| ((next, maze.pick_key(next, keys)), step))
+    }
+}
+
+impl MyApp {
+    // В дверь можно войти, только имея ключ её цвета
+    pub(super) fn can_enter(&self, cell: (usize, usize), keys: u32) -> bool {
+        self.doors.get(&cell).is_none_or(|&color| keys & (1 << color) != 0)
+    }
+
+    // Ключи после шага на cell: если там лежит ключ, он подбирается
+    pub(super) fn pick_key(&self, cell: (usize, usize), keys: u32) -> u32 {
+        keys | self.keys.get(&cell).map_or(0, |&color| 1 << color)
+    }
+
+    // Разделяет шаги (клетка, ключи) на путь и порядок подобранных ключей
+    pub(super) fn split_key_steps(&self, steps: Vec<KeyState>) -> KeyedPath {
+        let key_order = steps
+            .windows(2)
+            .filter(|step| step[0].1 != step[1].1)
+            .filter_map(|step| self.keys.get(&step[1].0).copied())
+            .collect();
+        (steps.into_iter().map(|(cell, _)| cell).collect(), key_order)
+    }
+
+    // Двери ставятся по пути к выходу, и каждая должна его перекрывать: без её ключа до выхода
+    // не дойти ни в обход, ни через портал. Ключ кладём туда, куда можно попасть, не открывая
+    // новой двери, и откуда можно вернуться к ней. Возвращает число поставленных дверей.
+    pub(super) fn place_keys_and_doors(&mut self, rng: &mut impl Rng) -> usize {
+        self.doors.clear();
+        self.keys.clear();
+        self.key_order.clear();
+
+        let path = self.dijkstra(self.entrance, self.exit);
+        let count = self.door_count.min(KEY_COLORS.len()).min(path.len().saturating_sub(2) / 2);
+        let mut colors: Vec<usize> = (0..KEY_COLORS.len()).collect();
+        colors.shuffle(rng);
+        let on_path: HashSet<(usize, usize)> = path.iter().copied().collect();
+        let bypassed = self.bypassed(&path);
+
+        // Клетки пути перебираем от равномерной отметки в обе стороны, но не раньше прошлой двери
+        let mut first = 1;
+        for (index, &color) in colors.iter().take(count).enumerate() {
+            let mark = (index + 1) * (path.len() - 1) / (count + 1);
+            let mut cells: Vec<usize> = (first..path.len() - 1).filter(|&cell| !bypassed[cell]).collect();
+            cells.sort_by_key(|&cell| cell.abs_diff(mark));
+            match cells.into_iter().take(DOOR_ATTEMPTS).find(|&cell| self.try_door(path[cell], color, &on_path, rng)) {
+                Some(cell) => first = cell + 1,
+                None => break,
+            }
+        }
+        self.doors.len()
+    }
+
+    // Для каждой клетки пути: можно ли пройти от входа к выходу мимо неё — прямым переходом
+    // через неё или через клетки вне пути. Направления коридоров не учитываем, поэтому
+    // клетка без обхода точно перекрывает выход.
+    fn bypassed(&self, path: &[(usize, usize)]) -> Vec<bool> {
+        let index: HashMap<(usize, usize), usize> = path.iter().enumerate().map(|(i, &cell)| (cell, i)).collect();
+        let mut links: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for row in 0..self.grid.len() {
+            for col in 0..self.grid[0].len() {
+                if self.grid[row][col] {
+                    continue;
+                }
+                for (next, _) in self.edges((row, col)) {
+                    links.entry((row, col)).or_default().push(next);
+                    links.entry(next).or_default().push((row, col));
+                }
+            }
+        }
+
+        // Отрезки пути (от, до), которые можно миновать
+        let mut spans = Vec::new();
+        let mut seen = HashSet::new();
+        for (&cell, next) in &links {
+            if let Some(&from) = index.get(&cell) {
+                spans.extend(next.iter().filter_map(|other| index.get(other)).map(|&to| (from.min(to), from.max(to))));
+                continue;
+            }
+            if !seen.insert(cell) {
+                continue;
+            }
+            // Область вне пути: она соединяет самую раннюю и самую позднюю клетку пути, которых касается
+            let (mut low, mut high) = (usize::MAX, 0);
+            let mut stack = vec![cell];
+            while let Some(current) = stack.pop() {
+                for other in &links[&current] {
+                    match index.get(other) {
+                        Some(&i) => (low, high) = (low.min(i), high.max(i)),
+                        None if seen.insert(*other) => stack.push(*other),
+                        None => {}
+                    }
+                }
+            }
+            if low < high {
+                spans.push((low, high));
+            }
+        }
+
+        let mut cover = vec![0i32; path.len() + 1];
+        for (low, high) in spans.into_iter().filter(|&(low, high)| high > low + 1) {
+            cover[low + 1] += 1;
+            cover[high] -= 1;
+        }
+        cover
+            .iter()
+            .take(path.len())
+            .scan(0, |depth, &change| {
+                *depth += change;
+                Some(*depth > 0)
+            })
+            .collect()
+    }
+
+    // Ставит дверь и ключ к ней; если дверь не перекрывает выход или лабиринт
+    // перестаёт быть проходимым, всё откатывает
+    fn try_door(&mut self, door: (usize, usize), color: usize, on_path: &HashSet<(usize, usize)>, rng: &mut impl Rng) -> bool {
+        self.doors.insert(door, color);
+        let mut seen = HashSet::new();
+        let before_door: Vec<(usize, usize)> = pathfinding::reachable(&KeyGraph(self), (self.entrance, 0))
+            .into_iter()
+            .map(|(cell, _)| cell)
+            .filter(|&cell| seen.insert(cell))
+            .collect();
+        if before_door.contains(&self.exit) {
+            self.doors.remove(&door);
+            return false;
+        }
+
+        let back_to_door = self.reaching(door);
+        let candidates: Vec<(usize, usize)> = before_door
+            .into_iter()
+            .filter(|cell| *cell != self.entrance && !self.keys.contains_key(cell) && !self.doors.contains_key(cell))
+            .filter(|cell| back_to_door.contains(cell))
+            .collect();
+        let dead_ends: Vec<(usize, usize)> = candidates
+            .iter()
+            .copied()
+            .filter(|&cell| !on_path.contains(&cell) && self.neighbors(cell).len() == 1)
+            .collect();
+
+        if let Some(&key) = dead_ends.choose(rng).or_else(|| candidates.choose(rng)) {
+            self.keys.insert(key, color);
+            if self.solve_with_keys().is_some() {
+                return true;
+            }
+            self.keys.remove(&key);
+        }
+        self.doors.remove(&door);
+        false
+    }
+
+    // Клетки, из которых можно дойти до to
+    fn reaching(&self, to: (usize, usize)) -> HashSet<(usize, usize)> {
+        let graph = StepGraph { maze: self, blocked: &[], moves: Moves::Backward };
+        pathfinding::reachable(&graph, to).into_iter().collect()
+    }
+
+    // Дейкстра по состояниям (клетка, ключи). Возвращает путь и порядок подобранных ключей.
+    pub(super) fn solve_with_keys(&self) -> Option<KeyedPath> {
+        let (states, _) = pathfinding::dijkstra(&KeyGraph(self), (self.entrance, 0), |(cell, _)| cell == self.exit)?;
+        Some(self.split_key_steps(states))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Status;
+
+    #[test]
+    fn every_door_blocks_the_way_to_the_exit() {
+        let modes: [fn(u64) -> MyApp; 3] = [
+            |seed| MyApp { seed, braid_percent: 30, ..MyApp::default() },
+            |seed| MyApp { seed, portal_count: 2, ..MyApp::default() },
+            |seed| MyApp { seed, open_grid: true, ..MyApp::default() },
+        ];
+        for mode in modes {
+            for seed in 0..30 {
+                let mut maze = MyApp { door_count: 3, ..mode(seed) };
+                maze.generate_maze().unwrap();
+                maze.solve_maze();
+                assert_eq!(maze.key_order.len(), maze.doors.len(), "seed {}", seed);
+                if maze.doors.len() < maze.door_count {
+                    assert!(matches!(maze.status, Status::Info(_)), "seed {}", seed);
+                }
+            }
+        }
+    }
+}
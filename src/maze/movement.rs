@@ -0,0 +1,43 @@
+use rand::Rng;
+
+use super::MyApp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl MyApp {
+    // Открытая карта: всё внутри рамки проходимо, кроме случайных препятствий
+    pub(super) fn carve_open_grid(&mut self, rng: &mut impl Rng, inside: &dyn Fn(usize, usize) -> bool) {
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        // Склеенные края — не рамка, по ним тоже можно ходить
+        let row_range = if self.topology.wraps_rows() { 0..rows } else { 1..rows - 1 };
+        let col_range = if self.topology.wraps_cols() { 0..cols } else { 1..cols - 1 };
+        for row in row_range {
+            for col in col_range.clone() {
+                self.grid[row][col] = !inside(row, col) || rng.gen_range(0..100) < self.obstacle_percent;
+            }
+        }
+    }
+
+    // Диагональный шаг стоит √2 и разрешён, только если обе клетки сбоку свободны —
+    // так путь не срезает углы стен
+    pub(super) fn diagonal_edges(&self, cell: (usize, usize)) -> Vec<((usize, usize), f64)> {
+        if self.connectivity == Connectivity::Four || self.one_way.contains_key(&cell) {
+            return Vec::new();
+        }
+
+        let is_free = |step: Option<(usize, usize)>| step.is_some_and(|(r, c)| !self.grid[r][c]);
+        [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .into_iter()
+            .filter_map(|(d_row, d_col)| {
+                let next = self.step(cell, (d_row, d_col))?;
+                let free = is_free(Some(next)) && is_free(self.step(cell, (0, d_col))) && is_free(self.step(cell, (d_row, 0)));
+                (free && !self.one_way.contains_key(&next)).then_some((next, std::f64::consts::SQRT_2))
+            })
+            .collect()
+    }
+}
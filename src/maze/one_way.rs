@@ -0,0 +1,55 @@
+use rand::{Rng, seq::SliceRandom};
+
+use super::MyApp;
+
+impl MyApp {
+    // Часть прямых коридоров делаем односторонними. Коридоры на пути к выходу
+    // ориентируем по ходу этого пути, поэтому выход всегда остаётся достижимым.
+    pub(super) fn place_one_way(&mut self, rng: &mut impl Rng) {
+        self.one_way.clear();
+
+        let path = self.dijkstra(self.entrance, self.exit);
+        let mut corridors: Vec<(usize, usize)> = (0..self.grid.len())
+            .flat_map(|row| (0..self.grid[0].len()).map(move |col| (row, col)))
+            .filter(|&cell| self.is_straight_corridor(cell))
+            .collect();
+        corridors.shuffle(rng);
+        corridors.truncate(corridors.len() * self.one_way_percent / 100);
+
+        for cell in corridors {
+            let direction = match path.iter().position(|&step| step == cell) {
+                Some(index) if index + 1 < path.len() => self.step_delta(cell, path[index + 1]),
+                _ => {
+                    let neighbor = *self.adjacent(cell).choose(rng).unwrap();
+                    self.step_delta(cell, neighbor)
+                }
+            };
+            self.one_way.insert(cell, direction);
+        }
+
+        if self.dijkstra(self.entrance, self.exit).is_empty() {
+            self.one_way.clear();
+        }
+    }
+
+    fn is_straight_corridor(&self, cell: (usize, usize)) -> bool {
+        // Клетки у входа в туннель под мостом не трогаем: шаг по туннелю длиной в две клетки
+        if self.grid[cell.0][cell.1] || cell == self.entrance || cell == self.exit || !self.under_passages(cell).is_empty() {
+            return false;
+        }
+        match self.adjacent(cell).as_slice() {
+            [a, b] => a.0 == b.0 || a.1 == b.1,
+            _ => false,
+        }
+    }
+
+    // Шаг запрещён, если он идёт против стрелки клетки, из которой или в которую шагаем
+    pub(super) fn allows_move(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        if self.one_way.is_empty() {
+            return true;
+        }
+        let (d_row, d_col) = self.step_delta(from, to);
+        let against = (-d_row, -d_col);
+        self.one_way.get(&from) != Some(&against) && self.one_way.get(&to) != Some(&against)
+    }
+}
@@ -0,0 +1,57 @@
+use eframe::egui;
+use rand::{Rng, seq::SliceRandom};
+
+use super::MyApp;
+
+pub const PORTAL_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(0, 206, 209),
+    egui::Color32::from_rgb(255, 105, 180),
+    egui::Color32::from_rgb(124, 252, 0),
+    egui::Color32::from_rgb(255, 140, 0),
+    egui::Color32::from_rgb(147, 112, 219),
+    egui::Color32::from_rgb(240, 230, 140),
+];
+
+impl MyApp {
+    // Порталы ставим парами в тупики, соединяя каждый с самым дальним из оставшихся
+    pub(super) fn place_portals(&mut self, rng: &mut impl Rng) {
+        self.portals.clear();
+
+        let endpoints = [self.entrance, self.exit];
+        let mut dead_ends: Vec<(usize, usize)> = (0..self.grid.len())
+            .flat_map(|row| (0..self.grid[0].len()).map(move |col| (row, col)))
+            .filter(|&cell| !self.grid[cell.0][cell.1] && !endpoints.contains(&cell) && self.neighbors(cell).len() == 1)
+            .collect();
+        dead_ends.shuffle(rng);
+
+        while self.portals.len() < self.portal_count && dead_ends.len() >= 2 {
+            let from = dead_ends.pop().unwrap();
+            let (index, _) = dead_ends
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, to)| from.0.abs_diff(to.0) + from.1.abs_diff(to.1))
+                .unwrap();
+            let to = dead_ends.swap_remove(index);
+            self.portals.push((from, to));
+        }
+    }
+
+    pub(super) fn portal_partner(&self, cell: (usize, usize)) -> Option<(usize, usize)> {
+        self.portals.iter().find_map(|&(a, b)| {
+            if a == cell {
+                Some(b)
+            } else if b == cell {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub(super) fn portal_color(&self, cell: (usize, usize)) -> Option<egui::Color32> {
+        self.portals
+            .iter()
+            .position(|&(a, b)| a == cell || b == cell)
+            .map(|index| PORTAL_COLORS[index % PORTAL_COLORS.len()])
+    }
+}
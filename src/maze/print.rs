@@ -0,0 +1,222 @@
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::MyApp;
+
+const COLUMNS: usize = 2;
+const ROWS: usize = 3;
+const MARGIN: f64 = 12.0;
+const LABEL_HEIGHT: f64 = 7.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageFormat {
+    A4,
+    Letter,
+}
+
+impl PageFormat {
+    // Размер страницы в миллиметрах
+    fn size(self) -> (f64, f64) {
+        match self {
+            PageFormat::A4 => (210.0, 297.0),
+            PageFormat::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+struct Puzzle {
+    number: usize,
+    seed: u64,
+    grid: Vec<Vec<bool>>,
+    path: Vec<(usize, usize)>,
+    crossings: HashMap<(usize, usize), (isize, isize)>,
+}
+
+impl MyApp {
+    // Пустой лабиринт с теми же настройками генерации, отличается только зерно
+    fn with_seed(&self, seed: u64) -> MyApp {
+        MyApp {
+            grid_size: self.grid_size,
+            seed,
+            mask_image: self.mask_image.clone(),
+            door_count: self.door_count,
+            gate_count: self.gate_count,
+            hazard_count: self.hazard_count,
+            connectivity: self.connectivity,
+            topology: self.topology,
+            open_grid: self.open_grid,
+            obstacle_percent: self.obstacle_percent,
+            one_way_percent: self.one_way_percent,
+            weave_percent: self.weave_percent,
+            portal_count: self.portal_count,
+            portal_cost: self.portal_cost,
+            braid_percent: self.braid_percent,
+            ..MyApp::default()
+        }
+    }
+
+    // Генерирует count лабиринтов с текущими настройками и раскладывает их
+    // по страницам: puzzles_N.svg — задания, answers_N.svg — ответы
+    pub(super) fn export_puzzle_sheets(&self, count: usize, format: PageFormat, folder: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut rng = thread_rng();
+        let mut puzzles = Vec::with_capacity(count);
+        for number in 1..=count {
+            let mut maze = self.with_seed(rng.gen());
+            maze.generate_maze().map_err(|err| io::Error::other(err.to_string()))?;
+            maze.solve_maze();
+            puzzles.push(Puzzle { number, seed: maze.seed, grid: maze.grid, path: maze.path, crossings: maze.crossings });
+        }
+
+        fs::create_dir_all(folder)?;
+        let mut files = Vec::new();
+        for (page, chunk) in puzzles.chunks(COLUMNS * ROWS).enumerate() {
+            for (prefix, with_answers) in [("puzzles", false), ("answers", true)] {
+                let file = folder.join(format!("{}_{}.svg", prefix, page + 1));
+                fs::write(&file, render_page(chunk, format, with_answers))?;
+                files.push(file);
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn render_page(puzzles: &[Puzzle], format: PageFormat, with_answers: bool) -> String {
+    let (width, height) = format.size();
+    let slot_width = (width - MARGIN * 2.0) / COLUMNS as f64;
+    let slot_height = (height - MARGIN * 2.0) / ROWS as f64;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height);
+
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        let slot_x = MARGIN + (index % COLUMNS) as f64 * slot_width;
+        let slot_y = MARGIN + (index / COLUMNS) as f64 * slot_height;
+        let rows = puzzle.grid.len();
+        let cols = puzzle.grid[0].len();
+        let cell = ((slot_width - 4.0) / cols as f64).min((slot_height - LABEL_HEIGHT - 4.0) / rows as f64);
+        let x0 = slot_x + (slot_width - cell * cols as f64) / 2.0;
+        let y0 = slot_y + LABEL_HEIGHT;
+
+        let title = if with_answers { "Ответ" } else { "Лабиринт" };
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" font-family="sans-serif" font-size="4">{} № {} · зерно {}</text>"#,
+            x0,
+            slot_y + LABEL_HEIGHT - 2.0,
+            title,
+            puzzle.number,
+            puzzle.seed
+        );
+
+        // Соседние стены в строке сливаем в один прямоугольник, чтобы файл оставался небольшим
+        for (row, cells) in puzzle.grid.iter().enumerate() {
+            let mut col = 0;
+            while col < cols {
+                if !cells[col] {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < cols && cells[col] {
+                    col += 1;
+                }
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}" fill="black"/>"#,
+                    x0 + start as f64 * cell,
+                    y0 + row as f64 * cell,
+                    (col - start) as f64 * cell,
+                    cell
+                );
+            }
+        }
+
+        // Мост: туннель под ним серый, а вдоль моста — перила
+        for (&(row, col), &(t_row, t_col)) in &puzzle.crossings {
+            for (u_row, u_col) in [(row as isize + t_row, col as isize + t_col), (row as isize - t_row, col as isize - t_col)] {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}" fill="lightgray"/>"#,
+                    x0 + u_col as f64 * cell,
+                    y0 + u_row as f64 * cell,
+                    cell,
+                    cell
+                );
+            }
+            let (x, y) = (x0 + col as f64 * cell, y0 + row as f64 * cell);
+            let rails = if t_row == 0 {
+                [(x, y, x, y + cell), (x + cell, y, x + cell, y + cell)]
+            } else {
+                [(x, y, x + cell, y), (x, y + cell, x + cell, y + cell)]
+            };
+            for (x1, y1, x2, y2) in rails {
+                let _ = writeln!(
+                    svg,
+                    r#"<line x1="{:.3}" y1="{:.3}" x2="{:.3}" y2="{:.3}" stroke="black" stroke-width="{:.3}"/>"#,
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    cell * 0.15
+                );
+            }
+        }
+
+        // Путь рвём там, где он уходит под мост, за шов или в портал
+        if with_answers && !puzzle.path.is_empty() {
+            let mut pieces = vec![vec![puzzle.path[0]]];
+            for step in puzzle.path.windows(2) {
+                if step[0].0.abs_diff(step[1].0) > 1 || step[0].1.abs_diff(step[1].1) > 1 {
+                    pieces.push(Vec::new());
+                }
+                pieces.last_mut().unwrap().push(step[1]);
+            }
+            for piece in pieces {
+                let points: Vec<String> = piece
+                    .iter()
+                    .map(|&(row, col)| format!("{:.3},{:.3}", x0 + (col as f64 + 0.5) * cell, y0 + (row as f64 + 0.5) * cell))
+                    .collect();
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline points="{}" fill="none" stroke="red" stroke-width="{:.3}" stroke-linejoin="round" stroke-linecap="round"/>"#,
+                    points.join(" "),
+                    cell * 0.4
+                );
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::topology::Topology;
+
+    #[test]
+    fn sheets_follow_current_settings() {
+        let maze = MyApp { grid_size: 25, weave_percent: 100, topology: Topology::Cylinder, ..MyApp::default() };
+        let sheet = maze.with_seed(7);
+        assert_eq!((sheet.seed, sheet.grid_size, sheet.weave_percent), (7, 25, 100));
+        assert_eq!(sheet.topology, maze.topology);
+
+        let folder = std::env::temp_dir().join("maze_print_test");
+        let files = maze.export_puzzle_sheets(1, PageFormat::A4, &folder).unwrap();
+        let answers = fs::read_to_string(&files[1]).unwrap();
+        assert!(answers.contains("<polyline"));
+        assert!(answers.contains("lightgray"), "мосты не нарисованы");
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use super::timed::{Gate, Hazard};
+use super::{topology, MyApp, GRID_SIZES};
+
+const SETTINGS_FILE: &str = "maze.txt";
+
+fn settings_path() -> io::Result<PathBuf> {
+    crate::app_dirs::config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "не найдена папка настроек"))
+}
+
+fn format_color(color: egui::Color32) -> String {
+    format!("{} {} {} {}", color.r(), color.g(), color.b(), color.a())
+}
+
+fn parse_color(value: &str) -> Option<egui::Color32> {
+    let parts: Vec<u8> = value.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match parts.as_slice() {
+        &[r, g, b, a] => Some(egui::Color32::from_rgba_premultiplied(r, g, b, a)),
+        _ => None,
+    }
+}
+
+fn format_cell((row, col): (usize, usize)) -> String {
+    format!("{} {}", row, col)
+}
+
+fn parse_cells(value: &str) -> Option<Vec<(usize, usize)>> {
+    let numbers: Vec<usize> = value.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if !numbers.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(numbers.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
+fn parse_cell(value: &str) -> Option<(usize, usize)> {
+    match parse_cells(value)?.as_slice() {
+        &[cell] => Some(cell),
+        _ => None,
+    }
+}
+
+impl MyApp {
+    // Файл построчный: «ключ=значение», строки сетки идут подряд с ключом grid
+    pub(super) fn save_settings(&self) -> io::Result<()> {
+        let mut lines = vec![
+            format!("path_color={}", format_color(self.path_color)),
+            format!("wall_color={}", format_color(self.wall_color)),
+            format!("passage_color={}", format_color(self.passage_color)),
+            format!("grid_size={}", self.grid_size),
+            format!("seed={}", self.seed),
+            format!("topology={:?}", self.topology),
+            format!("entrance={}", format_cell(self.entrance)),
+            format!("exit={}", format_cell(self.exit)),
+        ];
+        for row in &self.grid {
+            lines.push(format!("grid={}", row.iter().map(|&wall| if wall { '#' } else { '.' }).collect::<String>()));
+        }
+        let cells = |cells: Vec<(usize, usize)>| cells.into_iter().map(format_cell).collect::<Vec<_>>().join(" ");
+        lines.push(format!("path={}", cells(self.path.clone())));
+        for (&cell, &color) in &self.doors {
+            lines.push(format!("door={} {}", format_cell(cell), color));
+        }
+        for (&cell, &color) in &self.keys {
+            lines.push(format!("key={} {}", format_cell(cell), color));
+        }
+        for &(from, to) in &self.portals {
+            lines.push(format!("portal={}", cells(vec![from, to])));
+        }
+        for (&cell, &(d_row, d_col)) in &self.one_way {
+            lines.push(format!("one_way={} {} {}", format_cell(cell), d_row, d_col));
+        }
+        for (&cell, &(d_row, d_col)) in &self.crossings {
+            lines.push(format!("crossing={} {} {}", format_cell(cell), d_row, d_col));
+        }
+        for gate in &self.gates {
+            lines.push(format!("gate={} {} {} {}", format_cell(gate.cell), gate.period, gate.open_for, gate.phase));
+        }
+        for hazard in &self.hazards {
+            lines.push(format!("hazard={}", cells(hazard.route.clone())));
+        }
+
+        let path = settings_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, lines.join("\n"))
+    }
+
+    // Возвращает false, если файла нет или сохранённый лабиринт повреждён —
+    // тогда цвета всё равно применяются, а лабиринт надо сгенерировать заново
+    pub(super) fn load_settings(&mut self) -> bool {
+        let text = match settings_path().and_then(fs::read_to_string) {
+            Ok(text) => text,
+            Err(_) => return false,
+        };
+
+        let mut grid: Vec<Vec<bool>> = Vec::new();
+        let mut path = Vec::new();
+        let (mut doors, mut keys, mut portals, mut one_way) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut crossings, mut gates, mut hazards) = (Vec::new(), Vec::new(), Vec::new());
+        for line in text.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            match key {
+                "path_color" => self.path_color = parse_color(value).unwrap_or(self.path_color),
+                "wall_color" => self.wall_color = parse_color(value).unwrap_or(self.wall_color),
+                "passage_color" => self.passage_color = parse_color(value).unwrap_or(self.passage_color),
+                "grid_size" => {
+                    // Размер вне диапазона ползунка или чётный — файл правили вручную, оставляем прежний
+                    self.grid_size = value
+                        .parse()
+                        .ok()
+                        .filter(|size| GRID_SIZES.contains(size) && !size.is_multiple_of(2))
+                        .unwrap_or(self.grid_size)
+                }
+                "seed" => self.seed = value.parse().unwrap_or(self.seed),
+                "topology" => {
+                    self.topology = match value {
+                        "Cylinder" => topology::Topology::Cylinder,
+                        "Torus" => topology::Topology::Torus,
+                        _ => topology::Topology::Plane,
+                    }
+                }
+                "entrance" => self.entrance = parse_cell(value).unwrap_or(self.entrance),
+                "exit" => self.exit = parse_cell(value).unwrap_or(self.exit),
+                "grid" => grid.push(value.chars().map(|c| c == '#').collect()),
+                "path" => path = parse_cells(value).unwrap_or_default(),
+                "door" => doors.push(value.to_owned()),
+                "key" => keys.push(value.to_owned()),
+                "portal" => portals.push(value.to_owned()),
+                "one_way" => one_way.push(value.to_owned()),
+                "crossing" => crossings.push(value.to_owned()),
+                "gate" => gates.push(value.to_owned()),
+                "hazard" => hazards.push(value.to_owned()),
+                _ => {}
+            }
+        }
+
+        let size = self.grid_size;
+        let in_grid = |&(row, col): &(usize, usize)| row < size && col < size;
+        if grid.len() != size || grid.iter().any(|row| row.len() != size)
+            || !in_grid(&self.entrance) || !in_grid(&self.exit) || !path.iter().all(in_grid)
+        {
+            return false;
+        }
+
+        let colored = |values: Vec<String>| -> Vec<((usize, usize), usize)> {
+            values
+                .iter()
+                .filter_map(|value| match *value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    [row, col, color] => Some(((row.parse().ok()?, col.parse().ok()?), color.parse().ok()?)),
+                    _ => None,
+                })
+                .filter(|(cell, color)| in_grid(cell) && *color < super::keys::KEY_COLORS.len())
+                .collect()
+        };
+        self.doors = colored(doors).into_iter().collect();
+        self.keys = colored(keys).into_iter().collect();
+        self.portals = portals
+            .iter()
+            .filter_map(|value| match parse_cells(value)?.as_slice() {
+                &[from, to] if in_grid(&from) && in_grid(&to) => Some((from, to)),
+                _ => None,
+            })
+            .collect();
+        let directed = |values: Vec<String>| -> HashMap<(usize, usize), (isize, isize)> {
+            values
+                .iter()
+                .filter_map(|value| match *value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    [row, col, d_row, d_col] => Some(((row.parse().ok()?, col.parse().ok()?), (d_row.parse().ok()?, d_col.parse().ok()?))),
+                    _ => None,
+                })
+                .filter(|(cell, _)| in_grid(cell))
+                .collect()
+        };
+        self.one_way = directed(one_way);
+        self.crossings = directed(crossings);
+        self.gates = gates
+            .iter()
+            .filter_map(|value| match *value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [row, col, period, open_for, phase] => Some(Gate {
+                    cell: (row.parse().ok()?, col.parse().ok()?),
+                    period: period.parse().ok()?,
+                    open_for: open_for.parse().ok()?,
+                    phase: phase.parse().ok()?,
+                }),
+                _ => None,
+            })
+            .filter(|gate| in_grid(&gate.cell) && gate.period > 0)
+            .collect();
+        self.hazards = hazards
+            .iter()
+            .filter_map(|value| parse_cells(value))
+            .filter(|route| !route.is_empty() && route.iter().all(in_grid))
+            .map(|route| Hazard { route })
+            .collect();
+        self.grid = grid;
+        self.path = path;
+        true
+    }
+}
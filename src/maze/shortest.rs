@@ -0,0 +1,162 @@
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+use super::{MazeGraph, MyApp, Status};
+use crate::pathfinding;
+
+// Сколько кратчайших путей перебираем явно; считаем — все
+pub const ENUMERATE_LIMIT: usize = 500;
+const EPSILON: f64 = 1e-9;
+
+// Для каждой клетки — соседи, через которых к ней ведут кратчайшие пути от входа
+type Predecessors = HashMap<(usize, usize), Vec<(usize, usize)>>;
+
+impl MyApp {
+    // Плетёный лабиринт: часть тупиков соединяем с соседней комнатой, появляются циклы
+    pub(super) fn braid(&mut self, rng: &mut impl Rng) {
+        // Без плетения генератор не трогаем: прежние зёрна дают прежние лабиринты
+        if self.braid_percent == 0 {
+            return;
+        }
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        for row in (1..rows - 1).step_by(2) {
+            for col in (1..cols - 1).step_by(2) {
+                if self.grid[row][col] || self.adjacent((row, col)).len() != 1 || rng.gen_range(0..100) >= self.braid_percent {
+                    continue;
+                }
+                let walls: Vec<Vec<(usize, usize)>> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .into_iter()
+                    .filter_map(|direction| {
+                        let (walls, room) = self.room_beyond((row, col), direction)?;
+                        (walls.iter().all(|&(w_row, w_col)| self.grid[w_row][w_col]) && !self.grid[room.0][room.1]).then_some(walls)
+                    })
+                    .collect();
+                if !walls.is_empty() {
+                    for (w_row, w_col) in walls[rng.gen_range(0..walls.len())].clone() {
+                        self.grid[w_row][w_col] = false;
+                    }
+                }
+            }
+        }
+    }
+
+    // Цены кратчайших путей от start и все предшественники с той же кратчайшей длиной.
+    // Предшественники каждой клетки идут по возрастанию цены, чтобы перебор путей был одинаковым от запуска к запуску.
+    fn shortest_path_dag(&self, start: (usize, usize)) -> (HashMap<(usize, usize), f64>, Predecessors) {
+        let graph = MazeGraph { maze: self, banned_nodes: &HashSet::new(), banned_edges: &HashSet::new() };
+        let distances = pathfinding::distances(&graph, start);
+
+        let mut cells: Vec<(usize, usize)> = distances.keys().copied().collect();
+        cells.sort_by(|a, b| distances[a].total_cmp(&distances[b]).then(a.cmp(b)));
+        let mut predecessors = Predecessors::new();
+        for cell in cells {
+            for (next, step) in self.edges(cell) {
+                if distances.get(&next).is_some_and(|&known| (distances[&cell] + step - known).abs() <= EPSILON) {
+                    predecessors.entry(next).or_default().push(cell);
+                }
+            }
+        }
+        (distances, predecessors)
+    }
+
+    // Считает кратчайшие пути от входа до выхода, перебирает первые ENUMERATE_LIMIT из них
+    // и для каждой клетки считает, сколько кратчайших путей через неё проходит
+    pub(super) fn analyze_shortest_paths(&mut self) {
+        self.shortest_paths.clear();
+        self.path_usage.clear();
+        self.shortest_count = 0;
+        self.shortest_index = 0;
+
+        let (distances, predecessors) = self.shortest_path_dag(self.entrance);
+        if !distances.contains_key(&self.exit) {
+            self.status = Status::Error("Путь не найден: выход недостижим".to_owned());
+            return;
+        }
+
+        let mut order: Vec<(usize, usize)> = distances.keys().copied().collect();
+        order.sort_by(|a, b| distances[a].total_cmp(&distances[b]));
+
+        let mut from_start: HashMap<(usize, usize), u128> = HashMap::from([(self.entrance, 1)]);
+        for cell in &order {
+            if let Some(prev) = predecessors.get(cell) {
+                let count = prev.iter().fold(0u128, |sum, p| sum.saturating_add(*from_start.get(p).unwrap_or(&0)));
+                from_start.insert(*cell, count);
+            }
+        }
+
+        let mut to_exit: HashMap<(usize, usize), u128> = HashMap::from([(self.exit, 1)]);
+        for cell in order.iter().rev() {
+            let count = match to_exit.get(cell) {
+                Some(&count) => count,
+                None => continue,
+            };
+            for prev in predecessors.get(cell).into_iter().flatten() {
+                let entry = to_exit.entry(*prev).or_insert(0);
+                *entry = entry.saturating_add(count);
+            }
+        }
+
+        self.shortest_count = from_start[&self.exit];
+        self.path_usage = to_exit
+            .iter()
+            .map(|(cell, &count)| (*cell, count.saturating_mul(*from_start.get(cell).unwrap_or(&0))))
+            .collect();
+
+        // Перебор от выхода к входу по предшественникам
+        let mut stack = vec![vec![self.exit]];
+        while let Some(partial) = stack.pop() {
+            if self.shortest_paths.len() >= ENUMERATE_LIMIT {
+                break;
+            }
+            let last = *partial.last().unwrap();
+            if last == self.entrance {
+                self.shortest_paths.push(partial.into_iter().rev().collect());
+                continue;
+            }
+            for prev in predecessors.get(&last).into_iter().flatten() {
+                let mut extended = partial.clone();
+                extended.push(*prev);
+                stack.push(extended);
+            }
+        }
+
+        self.path = self.shortest_paths[0].clone();
+    }
+
+    pub(super) fn show_shortest_path(&mut self, index: usize) {
+        if !self.shortest_paths.is_empty() {
+            self.shortest_index = index % self.shortest_paths.len();
+            self.path = self.shortest_paths[self.shortest_index].clone();
+        }
+    }
+
+    pub(super) fn clear_shortest_paths(&mut self) {
+        self.shortest_paths.clear();
+        self.path_usage.clear();
+        self.shortest_count = 0;
+        self.shortest_index = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn braid_off_keeps_seeded_mazes() {
+        let mut maze = MyApp { seed: 42, ..MyApp::default() };
+        maze.generate_maze().unwrap();
+        let grid = maze.grid.clone();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut untouched = rng.clone();
+        maze.braid(&mut rng);
+        assert_eq!(maze.grid, grid);
+        assert_eq!(rng.gen::<u64>(), untouched.gen::<u64>());
+
+        maze.generate_maze().unwrap();
+        assert_eq!(maze.grid, grid);
+    }
+}
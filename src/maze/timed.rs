@@ -0,0 +1,208 @@
+use rand::{Rng, seq::SliceRandom};
+
+use super::MyApp;
+use super::keys::KeyedPath;
+use crate::pathfinding::{self, Graph};
+
+// Длительность одного такта анимации, в секундах
+pub const TICK_SECONDS: f64 = 0.25;
+
+// Ворота на клетке: открыты первые open_for тактов каждого периода
+#[derive(Debug, Clone)]
+pub struct Gate {
+    pub cell: (usize, usize),
+    pub period: usize,
+    pub open_for: usize,
+    pub phase: usize,
+}
+
+impl Gate {
+    pub fn is_open(&self, tick: usize) -> bool {
+        (tick + self.phase) % self.period < self.open_for
+    }
+}
+
+// Патруль ходит по замкнутому маршруту, на каждом такте сдвигаясь на одну клетку
+#[derive(Debug, Clone)]
+pub struct Hazard {
+    pub route: Vec<(usize, usize)>,
+}
+
+impl Hazard {
+    pub fn position(&self, tick: usize) -> (usize, usize) {
+        self.route[tick % self.route.len()]
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Сколько тактов длится переход ценой cost: диагональ и туннель — два такта, портал — сколько стоит
+fn ticks_for(cost: f64) -> usize {
+    (cost.ceil() as usize).max(1)
+}
+
+impl MyApp {
+    pub(super) fn place_gates_and_hazards(&mut self, rng: &mut impl Rng) {
+        self.gates.clear();
+        self.hazards.clear();
+
+        let endpoints = [self.entrance, self.exit];
+        let corridors: Vec<(usize, usize)> = (0..self.grid.len())
+            .flat_map(|row| (0..self.grid[0].len()).map(move |col| (row, col)))
+            .filter(|&cell| !self.grid[cell.0][cell.1] && !endpoints.contains(&cell) && self.neighbors(cell).len() == 2)
+            .collect();
+
+        // Ворота ставим в первую очередь на путь к выходу, чтобы их приходилось ждать
+        let path = self.dijkstra(self.entrance, self.exit);
+        let mut gate_cells: Vec<(usize, usize)> = path.iter().copied().filter(|cell| corridors.contains(cell)).collect();
+        gate_cells.shuffle(rng);
+        gate_cells.truncate(self.gate_count);
+        for &cell in &gate_cells {
+            let period = *[4, 6, 8].choose(rng).unwrap();
+            self.gates.push(Gate { cell, period, open_for: period / 2, phase: rng.gen_range(0..period) });
+        }
+
+        // Патрули ходят только вне пути к выходу и мимо ключей, иначе в тупиковом коридоре с ними не разминуться
+        let off_path: Vec<(usize, usize)> = corridors
+            .iter()
+            .copied()
+            .filter(|cell| !path.contains(cell) && !self.keys.contains_key(cell))
+            .collect();
+        for _ in 0..self.hazard_count {
+            let start = match off_path.choose(rng) {
+                Some(&cell) => cell,
+                None => break,
+            };
+            let length = rng.gen_range(3..=5);
+            let mut segment = vec![start];
+            while segment.len() < length {
+                let last = *segment.last().unwrap();
+                let options: Vec<(usize, usize)> = self
+                    .neighbors(last)
+                    .into_iter()
+                    .filter(|cell| !segment.contains(cell) && !path.contains(cell) && !self.keys.contains_key(cell))
+                    .collect();
+                match options.choose(rng) {
+                    Some(&next) => segment.push(next),
+                    None => break,
+                }
+            }
+            if segment.len() < 2 {
+                continue;
+            }
+
+            // Туда и обратно: a b c d c b — получается петля
+            let mut route = segment.clone();
+            route.extend(segment[1..segment.len() - 1].iter().rev());
+            self.hazards.push(Hazard { route });
+        }
+    }
+
+    fn is_safe(&self, cell: (usize, usize), tick: usize) -> bool {
+        self.gates.iter().all(|gate| gate.cell != cell || gate.is_open(tick))
+            && self.hazards.iter().all(|hazard| hazard.position(tick) != cell)
+    }
+
+    // Поиск в пространстве-времени: состояние — клетка, такт и подобранные ключи, можно стоять на месте.
+    // Расписания периодичны, поэтому такт храним по модулю общего периода.
+    // Возвращает путь и порядок подобранных ключей, как solve_with_keys.
+    pub(super) fn solve_timed(&self) -> Option<KeyedPath> {
+        let cycle = self
+            .gates
+            .iter()
+            .map(|gate| gate.period)
+            .chain(self.hazards.iter().map(|hazard| hazard.route.len()))
+            .fold(1, |acc, period| acc / gcd(acc, period) * period);
+
+        let graph = TimedGraph { maze: self, cycle };
+        let (states, _) = pathfinding::dijkstra(&graph, (self.entrance, 0, 0), |(cell, _, _)| cell == self.exit)?;
+        let steps = states.into_iter().map(|(cell, _, keys)| (cell, keys)).collect();
+        Some(self.split_key_steps(steps))
+    }
+
+    // Такт, на котором путь приходит в каждую свою клетку: ожидание занимает такт,
+    // переход — столько тактов, сколько он стоит
+    pub(super) fn arrival_ticks(&self, path: &[(usize, usize)]) -> Vec<usize> {
+        let mut tick = 0;
+        let mut arrivals: Vec<usize> = path.first().map(|_| 0).into_iter().collect();
+        for step in path.windows(2) {
+            let cost = self.edges(step[0]).into_iter().find(|&(next, _)| next == step[1]).map_or(1.0, |(_, cost)| cost);
+            tick += ticks_for(cost);
+            arrivals.push(tick);
+        }
+        arrivals
+    }
+}
+
+// Переход занимает столько тактов, сколько стоит, поэтому самый ранний приход к выходу ищем Дейкстрой
+struct TimedGraph<'a> {
+    maze: &'a MyApp,
+    cycle: usize,
+}
+
+// Клетка, такт по модулю периода и ключи (бит на цвет)
+type TimedState = ((usize, usize), usize, u32);
+
+impl Graph for TimedGraph<'_> {
+    type Node = TimedState;
+
+    fn neighbors(&self, (cell, tick, keys): TimedState) -> impl Iterator<Item = (TimedState, f64)> {
+        let maze = self.maze;
+        let mut moves = maze.edges(cell);
+        moves.push((cell, 1.0)); // ожидание на месте
+        moves.into_iter().filter_map(move |(next, cost)| {
+            let ticks = ticks_for(cost);
+            let arrival = tick + ticks;
+            // Пока переход длится, стоим в исходной клетке; меняться местами с патрулём нельзя
+            let stays_safe = (tick + 1..arrival).all(|during| maze.is_safe(cell, during));
+            let swaps = maze.hazards.iter().any(|hazard| {
+                hazard.position(arrival - 1) == next && hazard.position(arrival) == cell
+            });
+            (stays_safe && maze.is_safe(next, arrival) && !swaps && maze.can_enter(next, keys))
+                .then(|| ((next, arrival % self.cycle, maze.pick_key(next, keys)), ticks as f64))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::movement::Connectivity;
+
+    #[test]
+    fn doors_with_gates_and_hazards_are_solvable() {
+        for seed in 0..20 {
+            let mut maze = MyApp { seed, door_count: 2, gate_count: 2, hazard_count: 2, ..MyApp::default() };
+            maze.generate_maze().unwrap();
+            assert!(!maze.doors.is_empty() && !maze.gates.is_empty(), "seed {}", seed);
+
+            maze.solve_maze();
+            assert_eq!(maze.path.first(), Some(&maze.entrance));
+            assert_eq!(maze.path.last(), Some(&maze.exit));
+            assert_eq!(maze.key_order.len(), maze.doors.len());
+        }
+    }
+    #[test]
+    fn timed_moves_keep_diagonals_and_portal_cost() {
+        let mut maze = MyApp {
+            connectivity: Connectivity::Eight,
+            portals: vec![((1, 1), (19, 19))],
+            portal_cost: 3,
+            entrance: (1, 1),
+            exit: (19, 19),
+            ..MyApp::default()
+        };
+        // Ворота открыты всегда: решение не должно отличаться от обычного
+        maze.gates.push(Gate { cell: (10, 10), period: 2, open_for: 2, phase: 0 });
+        let (path, _) = maze.solve_timed().unwrap();
+        assert_eq!(path, [(1, 1), (19, 19)]);
+        assert_eq!(maze.arrival_ticks(&path), [0, 3]);
+
+        maze.exit = (2, 2);
+        let (path, _) = maze.solve_timed().unwrap();
+        assert_eq!(path, [(1, 1), (2, 2)]);
+        assert_eq!(maze.arrival_ticks(&path), [0, 2]);
+    }
+}
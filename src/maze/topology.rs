@@ -0,0 +1,83 @@
+use super::{Moves, MyApp, StepGraph};
+use crate::pathfinding;
+
+// Склейка краёв: цилиндр соединяет левый и правый край, тор — ещё и верхний с нижним
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    Plane,
+    Cylinder,
+    Torus,
+}
+
+impl Topology {
+    pub fn wraps_cols(self) -> bool {
+        self != Topology::Plane
+    }
+
+    pub fn wraps_rows(self) -> bool {
+        self == Topology::Torus
+    }
+}
+
+// Стены до соседней комнаты и сама комната
+type Beyond = (Vec<(usize, usize)>, (usize, usize));
+
+impl MyApp {
+    // Клетка на шаг (d_row, d_col) от cell с учётом склейки; None — шаг за внешнюю рамку
+    pub(super) fn step(&self, (row, col): (usize, usize), (d_row, d_col): (isize, isize)) -> Option<(usize, usize)> {
+        let rows = self.grid.len() as isize;
+        let cols = self.grid[0].len() as isize;
+        let mut n_row = row as isize + d_row;
+        let mut n_col = col as isize + d_col;
+        if self.topology.wraps_rows() {
+            n_row = n_row.rem_euclid(rows);
+        }
+        if self.topology.wraps_cols() {
+            n_col = n_col.rem_euclid(cols);
+        }
+        ((0..rows).contains(&n_row) && (0..cols).contains(&n_col)).then_some((n_row as usize, n_col as usize))
+    }
+
+    // Смещение между соседними клетками: шаг через шов даёт ±1, а не ±(размер - 1)
+    pub(super) fn step_delta(&self, from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+        let unwrap = |d: isize, size: usize, wraps: bool| if wraps && d.unsigned_abs() == size - 1 { -d.signum() } else { d };
+        (
+            unwrap(to.0 as isize - from.0 as isize, self.grid.len(), self.topology.wraps_rows()),
+            unwrap(to.1 as isize - from.1 as isize, self.grid[0].len(), self.topology.wraps_cols()),
+        )
+    }
+
+    // Соседняя комната (обе координаты нечётные) и стены между ней и room.
+    // На шве между комнатами две стены: последний столбец и нулевой.
+    pub(super) fn room_beyond(&self, room: (usize, usize), direction: (isize, isize)) -> Option<Beyond> {
+        let mut walls = Vec::new();
+        let mut cell = self.step(room, direction)?;
+        while cell.0 % 2 == 0 || cell.1 % 2 == 0 {
+            walls.push(cell);
+            cell = self.step(cell, direction)?;
+        }
+        Some((walls, cell))
+    }
+
+    // Клетка внешней рамки, которую не склеивают с противоположным краем
+    pub(super) fn is_outer_border(&self, (row, col): (usize, usize)) -> bool {
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        (!self.topology.wraps_rows() && (row == 0 || row == rows - 1))
+            || (!self.topology.wraps_cols() && (col == 0 || col == cols - 1))
+    }
+
+    // Расстояние по сетке без учёта стен, с учётом склейки
+    pub(super) fn grid_distance(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+        let (d_row, d_col) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        let d_row = if self.topology.wraps_rows() { d_row.min(self.grid.len() - d_row) } else { d_row };
+        let d_col = if self.topology.wraps_cols() { d_col.min(self.grid[0].len() - d_col) } else { d_col };
+        d_row + d_col
+    }
+
+    // Самая далёкая по ходам клетка от start — на торе туда ставим выход
+    pub(super) fn farthest_cell(&self, start: (usize, usize)) -> (usize, usize) {
+        let graph = StepGraph { maze: self, blocked: &[], moves: Moves::Undirected };
+        pathfinding::reachable(&graph, start).last().copied().unwrap_or(start)
+    }
+}
@@ -0,0 +1,83 @@
+use super::{MyApp, Status};
+use crate::pathfinding::{self, Graph};
+
+// Состояние поиска: клетка и число уже разбитых стен
+type BreakState = ((usize, usize), usize);
+
+// Шаг в стену стоит столько же, сколько шаг по проходу, но расходует одну стену из бюджета.
+// Внешнюю рамку ломать нельзя.
+struct BreakGraph<'a> {
+    maze: &'a MyApp,
+    budget: usize,
+}
+
+impl Graph for BreakGraph<'_> {
+    type Node = BreakState;
+
+    fn neighbors(&self, (cell, broken): BreakState) -> impl Iterator<Item = (BreakState, f64)> {
+        let maze = self.maze;
+        let around = [(-1, 0), (1, 0), (0, -1), (0, 1)].into_iter().filter_map(move |direction| maze.step(cell, direction));
+        around.chain(maze.under_passages(cell)).filter_map(move |next| {
+            let next_broken = if !maze.grid[next.0][next.1] {
+                if !maze.allows_move(cell, next) || maze.crosses_bridge(cell, next) {
+                    return None;
+                }
+                broken
+            } else {
+                if maze.is_outer_border(next) || broken == self.budget {
+                    return None;
+                }
+                broken + 1
+            };
+            Some(((next, next_broken), 1.0))
+        })
+    }
+}
+
+impl MyApp {
+    // Поиск в ширину по состояниям (клетка, разбито стен) для каждого бюджета от 0 до wall_budget:
+    // запоминаем длину лучшего пути при каждом бюджете, а показываем самый короткий путь,
+    // и из равных по длине — тот, где разбито меньше стен
+    pub(super) fn solve_breaking_walls(&mut self) {
+        let mut best: Option<Vec<BreakState>> = None;
+        self.break_lengths.clear();
+        for budget in 0..=self.wall_budget {
+            let graph = BreakGraph { maze: self, budget };
+            let path = pathfinding::bfs(&graph, (self.entrance, 0), |(cell, _)| cell == self.exit);
+            self.break_lengths.push(path.as_ref().map(|path| path.len() - 1));
+            if let Some(path) = path {
+                if best.as_ref().is_none_or(|best| path.len() < best.len()) {
+                    best = Some(path);
+                }
+            }
+        }
+
+        let Some(states) = best else {
+            self.path.clear();
+            self.broken_walls.clear();
+            self.status = Status::Error("Путь не найден даже с разбиванием стен".to_owned());
+            return;
+        };
+        self.broken_walls = states.windows(2).filter(|step| step[0].1 != step[1].1).map(|step| step[1].0).collect();
+        self.path = states.into_iter().map(|(cell, _)| cell).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaking_walls_never_makes_the_path_longer() {
+        let mut maze = MyApp { seed: 5, wall_budget: 3, ..MyApp::default() };
+        maze.generate_maze().unwrap();
+        let plain = maze.dijkstra(maze.entrance, maze.exit).len() - 1;
+
+        maze.solve_breaking_walls();
+        assert_eq!(maze.break_lengths[0], Some(plain));
+        assert!(maze.break_lengths.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(Some(maze.path.len() - 1), maze.break_lengths[3]);
+        assert!(maze.broken_walls.len() <= 3);
+        assert!(maze.broken_walls.iter().all(|&(row, col)| maze.grid[row][col] && !maze.is_outer_border((row, col))));
+    }
+}
@@ -0,0 +1,146 @@
+use rand::Rng;
+
+use super::MyApp;
+
+// Мосты: над клеткой-перекрёстком проходит коридор, а под ней — туннель поперёк.
+// В crossings для каждого перекрёстка хранится направление туннеля, например (0, 1) —
+// туннель идёт слева направо, а мост сверху вниз.
+impl MyApp {
+    // После вырезания прокладываем туннели под прямыми коридорами. Туннель замыкает
+    // петлю, поэтому на старом пути между его концами снова ставим одну стену —
+    // лабиринт остаётся идеальным, без циклов.
+    pub(super) fn weave(&mut self, rng: &mut impl Rng, inside: &dyn Fn(usize, usize) -> bool) {
+        if self.weave_percent == 0 {
+            return;
+        }
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        for row in (1..rows - 1).step_by(2) {
+            for col in (1..cols - 1).step_by(2) {
+                for tunnel in [(0, 1), (1, 0)] {
+                    if self.grid[row][col] || self.crossings.contains_key(&(row, col)) {
+                        continue;
+                    }
+                    let Some((entry, exit)) = self.tunnel_ends((row, col), tunnel, inside) else {
+                        continue;
+                    };
+                    if rng.gen_range(0..100) >= self.weave_percent {
+                        continue;
+                    }
+
+                    let loop_path = self.dijkstra(entry, exit);
+                    let walls: Vec<(usize, usize)> = loop_path
+                        .iter()
+                        .copied()
+                        .filter(|&cell| (cell.0 % 2 == 0) != (cell.1 % 2 == 0) && self.is_plain_wall_gap(cell, (row, col)))
+                        .collect();
+                    if walls.is_empty() {
+                        continue;
+                    }
+                    let (w_row, w_col) = walls[rng.gen_range(0..walls.len())];
+                    self.grid[w_row][w_col] = true;
+
+                    for (t_row, t_col) in self.tunnel_walls((row, col), tunnel) {
+                        self.grid[t_row][t_col] = false;
+                    }
+                    self.crossings.insert((row, col), tunnel);
+                }
+            }
+        }
+    }
+
+    // Комнаты по обе стороны перекрёстка, если room — прямой коридор поперёк туннеля
+    // и стены под будущим туннелем ещё целы
+    fn tunnel_ends(
+        &self,
+        room: (usize, usize),
+        tunnel: (isize, isize),
+        inside: &dyn Fn(usize, usize) -> bool,
+    ) -> Option<((usize, usize), (usize, usize))> {
+        let across = (tunnel.1, tunnel.0);
+        let is_open = |d: (isize, isize)| self.step(room, d).is_some_and(|(r, c)| !self.grid[r][c]);
+        if !is_open(across) || !is_open((-across.0, -across.1)) || self.adjacent(room).len() != 2 {
+            return None;
+        }
+
+        let mut ends = Vec::new();
+        for direction in [tunnel, (-tunnel.0, -tunnel.1)] {
+            let (walls, beyond) = self.room_beyond(room, direction)?;
+            match walls.as_slice() {
+                &[(w_row, w_col)] if self.grid[w_row][w_col] && inside(w_row, w_col) => {}
+                _ => return None,
+            }
+            if self.grid[beyond.0][beyond.1] {
+                return None;
+            }
+            ends.push(beyond);
+        }
+        Some((ends[0], ends[1]))
+    }
+
+    fn tunnel_walls(&self, room: (usize, usize), tunnel: (isize, isize)) -> Vec<(usize, usize)> {
+        [tunnel, (-tunnel.0, -tunnel.1)].into_iter().filter_map(|direction| self.step(room, direction)).collect()
+    }
+
+    // Проём между двумя комнатами, который можно снова заложить: не туннель,
+    // не конец моста (ни готового, ни строящегося crossing) и не на шве склеенных краёв
+    fn is_plain_wall_gap(&self, cell: (usize, usize), crossing: (usize, usize)) -> bool {
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        let on_edge = cell.0 == 0 || cell.1 == 0 || cell.0 == rows - 1 || cell.1 == cols - 1;
+        let near_crossing = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(|direction| self.step(cell, direction))
+            .any(|next| next == crossing || self.crossings.contains_key(&next));
+        !on_edge && !near_crossing
+    }
+
+    // Шаг между перекрёстком и клеткой туннеля запрещён: туннель проходит под мостом
+    pub(super) fn crosses_bridge(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let (d_row, _) = self.step_delta(from, to);
+        let along_tunnel = |cell| self.crossings.get(&cell).is_some_and(|&(t_row, _)| (t_row != 0) == (d_row != 0));
+        along_tunnel(from) || along_tunnel(to)
+    }
+
+    // Клетки по ту сторону моста, куда можно пройти туннелем из cell
+    pub(super) fn under_passages(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        if self.crossings.is_empty() || self.grid[cell.0][cell.1] {
+            return Vec::new();
+        }
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(|direction: (isize, isize)| {
+                let bridge = self.step(cell, direction)?;
+                let &tunnel = self.crossings.get(&bridge)?;
+                (tunnel == (direction.0.abs(), direction.1.abs())).then_some(())?;
+                self.step(bridge, direction)
+            })
+            .collect()
+    }
+
+    pub(super) fn is_under_passage(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        self.under_passages(from).contains(&to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossings_keep_all_four_sides_open() {
+        let mut woven = 0;
+        for seed in 0..20 {
+            let mut maze = MyApp { seed, grid_size: 31, weave_percent: 100, ..MyApp::default() };
+            maze.generate_maze().unwrap();
+            woven += maze.crossings.len();
+            for &cell in maze.crossings.keys() {
+                for direction in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (row, col) = maze.step(cell, direction).unwrap();
+                    assert!(!maze.grid[row][col], "seed {}: мост {:?} закрыт с {:?}", seed, cell, direction);
+                }
+            }
+        }
+        assert!(woven > 0);
+    }
+}
@@ -0,0 +1,68 @@
+use eframe::egui;
+use std::collections::HashSet;
+
+use super::{Edge, MyApp};
+
+pub const ROUTE_COLORS: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(0, 200, 83),
+    egui::Color32::from_rgb(41, 121, 255),
+    egui::Color32::from_rgb(255, 171, 0),
+    egui::Color32::from_rgb(213, 0, 249),
+    egui::Color32::from_rgb(0, 229, 255),
+    egui::Color32::from_rgb(255, 61, 0),
+    egui::Color32::from_rgb(174, 234, 0),
+    egui::Color32::from_rgb(255, 64, 129),
+];
+
+impl MyApp {
+    // Алгоритм Йена: K кратчайших простых путей от входа к выходу.
+    // Каждый следующий путь ищется ответвлением от уже найденных.
+    pub(super) fn k_shortest_paths(&self, k: usize) -> Vec<(Vec<(usize, usize)>, f64)> {
+        let first = self.dijkstra(self.entrance, self.exit);
+        if first.is_empty() {
+            return Vec::new();
+        }
+
+        let mut found = vec![(first.clone(), self.path_length(&first))];
+        let mut candidates: Vec<(Vec<(usize, usize)>, f64)> = Vec::new();
+
+        while found.len() < k {
+            let last = found.last().unwrap().0.clone();
+            for spur_index in 0..last.len() - 1 {
+                let spur = last[spur_index];
+                let root = &last[..=spur_index];
+
+                // Запрещаем рёбра, по которым уже найденные пути уходят с того же корня
+                let banned_edges: HashSet<Edge> = found
+                    .iter()
+                    .map(|(path, _)| path)
+                    .filter(|path| path.len() > spur_index + 1 && &path[..=spur_index] == root)
+                    .map(|path| (path[spur_index], path[spur_index + 1]))
+                    .collect();
+                let banned_nodes: HashSet<(usize, usize)> = root[..spur_index].iter().copied().collect();
+
+                let spur_path = self.dijkstra_avoiding(spur, self.exit, &banned_nodes, &banned_edges);
+                if spur_path.is_empty() {
+                    continue;
+                }
+
+                let mut candidate = root[..spur_index].to_vec();
+                candidate.extend(spur_path);
+                let known = found.iter().chain(candidates.iter()).any(|(path, _)| *path == candidate);
+                if !known {
+                    let length = self.path_length(&candidate);
+                    candidates.push((candidate, length));
+                }
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+            let best = (0..candidates.len())
+                .min_by(|&a, &b| candidates[a].1.total_cmp(&candidates[b].1))
+                .unwrap();
+            found.push(candidates.swap_remove(best));
+        }
+        found
+    }
+}
@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+// Общий поиск пути для всех игр: лабиринта, змейки и будущих.
+// Граф задаётся только соседями узла и ценой перехода к каждому из них.
+pub trait Graph {
+    type Node: Copy + Eq + Hash;
+
+    fn neighbors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, f64)>;
+}
+
+// Узел в очереди с приоритетом. Равенство и порядок берутся из одной оценки,
+// иначе BinaryHeap может счесть равными записи, которые упорядочивает по-разному.
+struct Entry<N> {
+    estimate: f64,
+    cost: f64,
+    node: N,
+}
+
+impl<N> PartialEq for Entry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for Entry<N> {}
+
+// Обратный порядок: BinaryHeap отдаёт узел с наименьшей оценкой
+impl<N> Ord for Entry<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl<N> PartialOrd for Entry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn build_path<N: Copy + Eq + Hash>(came_from: &HashMap<N, N>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    let mut current = end;
+    while let Some(&prev) = came_from.get(&current) {
+        path.push(prev);
+        current = prev;
+    }
+    path.reverse();
+    path
+}
+
+// Итог поиска: найденная цель с ценой пути до неё, лучшие известные цены и откуда пришли в каждый узел
+struct Search<N> {
+    goal: Option<(N, f64)>,
+    distances: HashMap<N, f64>,
+    came_from: HashMap<N, N>,
+}
+
+// Общий цикл A* и Дейкстры. Без цели обходит весь достижимый граф, и тогда distances —
+// точные цены кратчайших путей.
+fn search<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl Fn(G::Node) -> bool,
+    heuristic: impl Fn(G::Node) -> f64,
+) -> Search<G::Node> {
+    let mut heap = BinaryHeap::new();
+    let mut distances: HashMap<G::Node, f64> = HashMap::from([(start, 0.0)]);
+    let mut came_from: HashMap<G::Node, G::Node> = HashMap::new();
+    heap.push(Entry { estimate: heuristic(start), cost: 0.0, node: start });
+
+    while let Some(Entry { cost, node, .. }) = heap.pop() {
+        if cost > distances[&node] {
+            continue;
+        }
+        if is_goal(node) {
+            return Search { goal: Some((node, cost)), distances, came_from };
+        }
+        for (next, step) in graph.neighbors(node) {
+            let next_cost = cost + step;
+            if next_cost < *distances.get(&next).unwrap_or(&f64::INFINITY) {
+                distances.insert(next, next_cost);
+                came_from.insert(next, node);
+                heap.push(Entry { estimate: next_cost + heuristic(next), cost: next_cost, node: next });
+            }
+        }
+    }
+    Search { goal: None, distances, came_from }
+}
+
+// A*: путь от start до первого узла, для которого is_goal вернул true, и его цена.
+// heuristic не должна переоценивать оставшийся путь, иначе путь может выйти не кратчайшим.
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl Fn(G::Node) -> bool,
+    heuristic: impl Fn(G::Node) -> f64,
+) -> Option<(Vec<G::Node>, f64)> {
+    let search = search(graph, start, is_goal, heuristic);
+    let (goal, cost) = search.goal?;
+    Some((build_path(&search.came_from, goal), cost))
+}
+
+pub fn dijkstra<G: Graph>(graph: &G, start: G::Node, is_goal: impl Fn(G::Node) -> bool) -> Option<(Vec<G::Node>, f64)> {
+    astar(graph, start, is_goal, |_| 0.0)
+}
+
+// Цены кратчайших путей от start до всех достижимых узлов
+pub fn distances<G: Graph>(graph: &G, start: G::Node) -> HashMap<G::Node, f64> {
+    search(graph, start, |_| false, |_| 0.0).distances
+}
+
+// Обход в ширину до первой цели. order — узлы в порядке, в котором их достал обход.
+fn breadth_first<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl Fn(G::Node) -> bool,
+    came_from: &mut HashMap<G::Node, G::Node>,
+    order: &mut Vec<G::Node>,
+) -> Option<G::Node> {
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        if is_goal(node) {
+            return Some(node);
+        }
+        for (next, _) in graph.neighbors(node) {
+            if seen.insert(next) {
+                came_from.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+// Поиск в ширину: цены рёбер не учитываются, путь кратчайший по числу шагов
+pub fn bfs<G: Graph>(graph: &G, start: G::Node, is_goal: impl Fn(G::Node) -> bool) -> Option<Vec<G::Node>> {
+    let mut came_from = HashMap::new();
+    let goal = breadth_first(graph, start, is_goal, &mut came_from, &mut Vec::new())?;
+    Some(build_path(&came_from, goal))
+}
+
+// Все узлы, достижимые из start, в порядке обхода в ширину: последний — один из самых далёких
+pub fn reachable<G: Graph>(graph: &G, start: G::Node) -> Vec<G::Node> {
+    let mut order = Vec::new();
+    breadth_first(graph, start, |_| false, &mut HashMap::new(), &mut order);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ориентированный граф из списка рёбер (откуда, куда, цена)
+    struct Edges(Vec<(u32, u32, f64)>);
+
+    impl Graph for Edges {
+        type Node = u32;
+
+        fn neighbors(&self, node: u32) -> impl Iterator<Item = (u32, f64)> {
+            self.0.iter().filter(move |edge| edge.0 == node).map(|edge| (edge.1, edge.2))
+        }
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let graph = Edges(vec![(0, 1, 1.0), (1, 2, 1.0), (3, 4, 1.0)]);
+        assert_eq!(dijkstra(&graph, 0, |node| node == 4), None);
+        assert_eq!(astar(&graph, 0, |node| node == 3, |_| 0.0), None);
+        assert_eq!(bfs(&graph, 0, |node| node == 4), None);
+        assert_eq!(reachable(&graph, 0), [0, 1, 2]);
+        assert!(!distances(&graph, 0).contains_key(&3));
+    }
+
+    #[test]
+    fn equal_cost_ties() {
+        // Два пути по 2.0 до узла 3: годится любой, но цена должна быть точной
+        let graph = Edges(vec![(0, 1, 1.0), (0, 2, 1.0), (1, 3, 1.0), (2, 3, 1.0)]);
+        let (path, cost) = dijkstra(&graph, 0, |node| node == 3).unwrap();
+        assert_eq!(cost, 2.0);
+        assert_eq!(path.len(), 3);
+        assert!(path == [0, 1, 3] || path == [0, 2, 3]);
+        assert_eq!(bfs(&graph, 0, |node| node == 3).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn non_uniform_weights() {
+        // Прямое ребро дороже обхода из трёх дробных шагов; BFS же берёт прямое
+        let graph = Edges(vec![(0, 3, 2.5), (0, 1, 0.5), (1, 2, 0.75), (2, 3, 0.25), (1, 3, 2.0)]);
+        let (path, cost) = dijkstra(&graph, 0, |node| node == 3).unwrap();
+        assert_eq!((path, cost), (vec![0, 1, 2, 3], 1.5));
+
+        let heuristic = |node: u32| [1.0, 0.5, 0.25, 0.0][node as usize];
+        assert_eq!(astar(&graph, 0, |node| node == 3, heuristic).unwrap(), (vec![0, 1, 2, 3], 1.5));
+        assert_eq!(bfs(&graph, 0, |node| node == 3).unwrap(), [0, 3]);
+        assert_eq!(distances(&graph, 0)[&2], 1.25);
+    }
+
+    #[test]
+    fn entry_equality_matches_order() {
+        let a = Entry { estimate: 1.0, cost: 0.0, node: 0 };
+        let b = Entry { estimate: 1.0, cost: 0.5, node: 1 };
+        let c = Entry { estimate: 2.0, cost: 0.0, node: 0 };
+        assert!(a == b && a.cmp(&b) == Ordering::Equal);
+        assert!(a != c && a > c);
+    }
+}
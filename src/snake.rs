@@ -1,18 +1,24 @@
 use std::collections::LinkedList;
 
-use piston_window::Context;
-use piston_window::G2d;
-use piston_window::types::Color;
+#[cfg(feature = "gui")]
+use piston_window::{Context, G2d, types::Color};
 
+#[cfg(feature = "gui")]
 use crate::drawing::draw_block;
 
+#[cfg(feature = "gui")]
 const SNAKE_COLOR: Color = [0.18, 0.80, 0.44, 1.0];
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Direction {
     Up, Down, Left, Right
 }
+// Содержимое клетки поля для наблюдения без графики
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tile {
+    Empty,
+    Wall,
+    SnakeHead,
     Snake,
     Food,
 }
@@ -62,6 +68,7 @@ impl Snake {
         }
     }
 
+    #[cfg(feature = "gui")]
     pub fn draw(&self, con: &Context, g: &mut G2d) {
         for block in &self.body {
             draw_block(SNAKE_COLOR, block.x, block.y, con, g);
@@ -136,6 +143,11 @@ impl Snake {
         self.body.push_back(blk);
     }
 
+    // Клетки тела от головы к хвосту
+    pub fn positions(&self) -> Vec<(i32, i32)> {
+        self.body.iter().map(|block| (block.x, block.y)).collect()
+    }
+
     pub fn is_overlap_except_tail(&self, x: i32, y: i32) -> bool {
         let mut checked = 0;
         for block in &self.body {
@@ -1,15 +1,11 @@
 extern crate piston_window;
 extern crate rand;
 
-mod snake;
-mod game;
-mod drawing;
-
 use piston_window::*;
 use piston_window::types::Color;
 
-use game::Game;
-use drawing::to_gui_coord_u32;
+use dijkstra_maze::game::Game;
+use dijkstra_maze::drawing::to_gui_coord_u32;
 
 const BACK_COLOR: Color = [0.204, 0.286, 0.369, 1.0];
 
@@ -19,14 +15,20 @@ fn main() {
     [to_gui_coord_u32(width), to_gui_coord_u32(height)]).exit_on_esc(true);
     window_settings.set_vsync(true); 
     let mut window: PistonWindow = window_settings.build().unwrap();
+    // Без шрифта игра идёт дальше, просто без строки состояния
+    let mut glyphs = window.load_font("pixel_font.ttf").ok();
     let mut game = Game::new(width, height);
     while let Some(event) = window.next() {
         if let Some(Button::Keyboard(key)) = event.press_args() {
             game.key_pressed(key);
         }
-        window.draw2d(&event, |c, g, | {
+        window.draw_2d(&event, |c, g, device| {
             clear(BACK_COLOR, g);
             game.draw(&c, g);
+            if let Some(glyphs) = glyphs.as_mut() {
+                game.draw_hud(&c, g, glyphs);
+                glyphs.factory.encoder.flush(device);
+            }
         });
         event.update(|arg| {
             game.update(arg.dt);